@@ -86,9 +86,57 @@ fn bench_cms_comparison(c: &mut Criterion) {
     group.finish();
 }
 
+fn bench_cms_large_distance(c: &mut Criterion) {
+    let mut group = c.benchmark_group("CMS_Large_Distance");
+    group.sample_size(20);
+
+    // Run with and without `--features rayon` to compare the serial and parallel row scans
+    let (w, d) = (1_048_576, 16);
+    let (cms_u, cms_n) = setup_distribution_sketches(w, d);
+    let mode = if cfg!(feature = "rayon") { "Parallel" } else { "Serial" };
+    let parameter_string = format!("{}/W{}xD{}", mode, w, d);
+
+    group.bench_function(BenchmarkId::new("Cosine_Similarity", &parameter_string), |b| {
+        b.iter(|| black_box(cms_u.cosine_similarity(black_box(&cms_n)).unwrap()))
+    });
+
+    group.bench_function(BenchmarkId::new("L1_Distance", &parameter_string), |b| {
+        b.iter(|| black_box(cms_u.l1_distance(black_box(&cms_n)).unwrap()))
+    });
+
+    group.finish();
+}
+
+fn bench_cms_merge_and_l1(c: &mut Criterion) {
+    let mut group = c.benchmark_group("CMS_Merge_And_L1");
+
+    // Run with and without `--features simd` to compare the scalar and lane-chunked loops
+    let (w, d) = (65536, 8);
+    let (cms_u, cms_n) = setup_distribution_sketches(w, d);
+    let mode = if cfg!(feature = "simd") { "Lanes" } else { "Scalar" };
+    let parameter_string = format!("{}/W{}xD{}", mode, w, d);
+
+    group.bench_function(BenchmarkId::new("Merge", &parameter_string), |b| {
+        b.iter_batched_ref(
+            || cms_u.clone(),
+            |cms| cms.merge(black_box(&cms_n)).unwrap(),
+            criterion::BatchSize::LargeInput,
+        )
+    });
+
+    group.bench_function(BenchmarkId::new("L1_Distance", &parameter_string), |b| {
+        b.iter(|| black_box(cms_u.l1_distance(black_box(&cms_n)).unwrap()))
+    });
+
+    group.finish();
+}
+
 fn bench_cms_full_load(c: &mut Criterion) {
     let mut group = c.benchmark_group("CountMinSketch_Performance");
 
+    // Run with and without `--features prefetch` to compare row prefetching, which matters
+    // most on the RAM heavy configuration
+
     let configurations : [(usize, usize); 3] = [
         (1024, 4),    // Cache L1/L2 friendly
         (65536, 8),   // Cache L3 boundary
@@ -129,12 +177,137 @@ fn bench_cms_full_load(c: &mut Criterion) {
     group.finish();
 }
 
+fn bench_cms_estimate_presence(c: &mut Criterion) {
+    let mut group = c.benchmark_group("CMS_Estimate_Present_vs_Absent");
+
+    let configurations: [(usize, usize); 2] = [
+        (65536, 8),   // Cache L3 boundary
+        (1048576, 16) // RAM heavy
+    ];
+
+    let dataset_size = 100_000;
+    let present = generate_random_strings(dataset_size, 16);
+    // Same shape as the present keys, but never inserted
+    let absent: Vec<String> = (0..dataset_size).map(|i| format!("absent_{:0>9}", i)).collect();
+
+    for (w, d) in configurations {
+        let mut cms = CountMinSketch::new(NonZeroUsize::try_from(w).unwrap(), NonZeroUsize::try_from(d).unwrap());
+        for key in &present {
+            cms.increment(key);
+        }
+        let parameter_string = format!("W{}xD{}", w, d);
+        group.throughput(Throughput::Elements(1));
+
+        group.bench_with_input(BenchmarkId::new("Present_Key", &parameter_string), &present, |b, keys| {
+            let mut i = 0;
+            b.iter(|| {
+                let key = &keys[i % dataset_size];
+                i += 1;
+                black_box(cms.estimate(black_box(key)))
+            });
+        });
+
+        group.bench_with_input(BenchmarkId::new("Absent_Key", &parameter_string), &absent, |b, keys| {
+            let mut i = 0;
+            b.iter(|| {
+                let key = &keys[i % dataset_size];
+                i += 1;
+                black_box(cms.estimate(black_box(key)))
+            });
+        });
+    }
+    group.finish();
+}
+
+fn bench_cms_batched_hasher(c: &mut Criterion) {
+    let mut group = c.benchmark_group("CMS_Batched_Hasher");
+
+    let (w, d) = (65536, 8);
+    let batch = generate_random_strings(1_000, 16);
+    let parameter_string = format!("W{}xD{}", w, d);
+    let mut cms = CountMinSketch::new(NonZeroUsize::try_from(w).unwrap(), NonZeroUsize::try_from(d).unwrap());
+    group.throughput(Throughput::Elements(batch.len() as u64));
+
+    group.bench_with_input(BenchmarkId::new("Per_Call_Increment", &parameter_string), &batch, |b, batch| {
+        b.iter(|| {
+            for key in batch {
+                cms.increment(black_box(key));
+            }
+        });
+    });
+
+    group.bench_with_input(BenchmarkId::new("Increment_Many", &parameter_string), &batch, |b, batch| {
+        b.iter(|| cms.increment_many(black_box(batch)));
+    });
+
+    group.finish();
+}
+
+fn bench_cms_depth_one(c: &mut Criterion) {
+    let mut group = c.benchmark_group("CMS_Depth_One");
+
+    let w = 65536;
+    let batch = generate_random_strings(1_000, 16);
+    group.throughput(Throughput::Elements(batch.len() as u64));
+
+    // Depth 1 takes the single-bucket fast path; depth 2 runs the generic double-hashing loop
+    for d in [1usize, 2] {
+        let parameter_string = format!("W{}xD{}", w, d);
+        let mut cms = CountMinSketch::new(NonZeroUsize::try_from(w).unwrap(), NonZeroUsize::try_from(d).unwrap());
+
+        group.bench_with_input(BenchmarkId::new("Increment", &parameter_string), &batch, |b, batch| {
+            b.iter(|| {
+                for key in batch {
+                    cms.increment(black_box(key));
+                }
+            });
+        });
+
+        group.bench_with_input(BenchmarkId::new("Estimate", &parameter_string), &batch, |b, batch| {
+            b.iter(|| {
+                for key in batch {
+                    black_box(cms.estimate(black_box(key)));
+                }
+            });
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_cms_clear(c: &mut Criterion) {
+    let mut group = c.benchmark_group("CMS_Clear");
+
+    let (w, d) = (65536, 8);
+    let batch = generate_random_strings(1_000, 16);
+    let parameter_string = format!("W{}xD{}", w, d);
+    let (width, depth) = (NonZeroUsize::try_from(w).unwrap(), NonZeroUsize::try_from(d).unwrap());
+    let mut cms = CountMinSketch::new(width, depth);
+
+    // A window rotation: reset, then refill. Rebuilding the sketch is the former `clear`
+    group.bench_with_input(BenchmarkId::new("Clear_In_Place", &parameter_string), &batch, |b, batch| {
+        b.iter(|| {
+            cms.clear();
+            cms.increment_many(black_box(batch));
+        });
+    });
+
+    group.bench_with_input(BenchmarkId::new("Reallocate", &parameter_string), &batch, |b, batch| {
+        b.iter(|| {
+            cms = CountMinSketch::new(width, depth);
+            cms.increment_many(black_box(batch));
+        });
+    });
+
+    group.finish();
+}
+
 criterion_group! {
     name = benches;
     config = Criterion::default()
         .significance_level(0.01)
         .measurement_time(Duration::from_secs(10))
         .warm_up_time(Duration::from_secs(2));
-    targets = bench_cms_full_load, bench_cms_comparison
+    targets = bench_cms_full_load, bench_cms_comparison, bench_cms_estimate_presence, bench_cms_batched_hasher, bench_cms_depth_one, bench_cms_large_distance, bench_cms_merge_and_l1, bench_cms_clear
 }
 criterion_main!(benches);
\ No newline at end of file
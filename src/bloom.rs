@@ -0,0 +1,55 @@
+use std::hash::Hash;
+
+use crate::CountMinSketch;
+use crate::hashing::SketchHasher;
+
+/// A membership filter derived from a [`CountMinSketch`], keeping one bit per cell.
+///
+/// Built by [`CountMinSketch::to_bloom`]: a bit is set where the sketch cell is nonzero, and
+/// keys are mapped with the sketch's own hashing, so [`contains`](Self::contains) answers
+/// exactly like [`CountMinSketch::contains`] did at conversion time, in 1/64 of the table
+/// memory. Counts are discarded and the view cannot be updated.
+pub struct CountingBloomView {
+    bits: Box<[u64]>,
+    width: usize,
+    width_mask: usize,
+    depth: usize,
+    row_offset: usize,
+    hasher: SketchHasher,
+}
+
+impl CountingBloomView {
+    /// Returns `true` if the item may have been inserted in the source sketch, `false` if it
+    /// definitely was not.
+    pub fn contains<T: Hash + ?Sized>(&self, item: &T) -> bool {
+        let h1 = self.hasher.hash_one(item);
+        let mut present = true;
+        <CountMinSketch>::calculate_indices(h1, self.row_offset, self.depth, self.width, self.width_mask, |idx| {
+            present &= self.bits[idx / 64] & (1 << (idx % 64)) != 0;
+        });
+        present
+    }
+
+    /// Returns the size of the bitmap in bytes.
+    pub fn bitmap_bytes(&self) -> usize {
+        self.bits.len() * std::mem::size_of::<u64>()
+    }
+}
+
+impl CountMinSketch {
+    /// Collapses the counter table into a [`CountingBloomView`] membership bitmap.
+    pub fn to_bloom(&self) -> CountingBloomView {
+        let mut bits = vec![0u64; self.table.len().div_ceil(64)].into_boxed_slice();
+        for (idx, _) in self.table.iter().enumerate().filter(|&(_, &cell)| cell > 0) {
+            bits[idx / 64] |= 1 << (idx % 64);
+        }
+        CountingBloomView {
+            bits,
+            width: self.width,
+            width_mask: self.width_mask,
+            depth: self.depth,
+            row_offset: self.row_offset,
+            hasher: self.hasher.clone(),
+        }
+    }
+}
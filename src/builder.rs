@@ -0,0 +1,153 @@
+use std::num::NonZeroUsize;
+
+use crate::error::BuildError;
+use crate::CountMinSketch;
+
+/// Error margin used when neither `width` nor `epsilon` is set.
+const DEFAULT_EPSILON: f64 = 0.001;
+/// Error probability used when neither `depth` nor `delta` is set.
+const DEFAULT_DELTA: f64 = 0.01;
+
+/// A builder for [`CountMinSketch`] taking plain `usize` dimensions or statistical parameters.
+///
+/// Each dimension is set either explicitly or from its parameter: `width` or `epsilon`
+/// (`width = ceil(e / epsilon)`), `depth` or `delta` (`depth = ceil(ln(1 / delta))`). Unset
+/// ones default to `epsilon = 0.001` and `delta = 0.01`. Both dimensions are rounded up to
+/// powers of two, as with [`CountMinSketch::new`]. Invalid input is reported by
+/// [`build`](Self::build) as a [`BuildError`] instead of a panic.
+///
+/// ```
+/// use count_min_sketch_rs::CountMinSketch;
+///
+/// let cms = CountMinSketch::builder().epsilon(0.01).delta(0.001).build().unwrap();
+/// assert_eq!((cms.get_width(), cms.get_depth()), (512, 8));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CountMinSketchBuilder {
+    width: Option<usize>,
+    depth: Option<usize>,
+    seeds: Option<[u64; 4]>,
+    epsilon: Option<f64>,
+    delta: Option<f64>,
+    sampling_rate: Option<f64>,
+}
+
+impl CountMinSketchBuilder {
+    /// Creates a builder with nothing set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the number of columns, rounded up to a power of two.
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// Sets the number of rows, rounded up to a power of two.
+    pub fn depth(mut self, depth: usize) -> Self {
+        self.depth = Some(depth);
+        self
+    }
+
+    /// Sets the hash seeds, see [`CountMinSketch::with_seeds`].
+    pub fn seeds(mut self, seeds: [u64; 4]) -> Self {
+        self.seeds = Some(seeds);
+        self
+    }
+
+    /// Sets the error margin the width is derived from.
+    pub fn epsilon(mut self, epsilon: f64) -> Self {
+        self.epsilon = Some(epsilon);
+        self
+    }
+
+    /// Sets the error probability the depth is derived from.
+    pub fn delta(mut self, delta: f64) -> Self {
+        self.delta = Some(delta);
+        self
+    }
+
+    /// Sets the fraction of the full stream the sketch is fed, e.g. `0.01` for a 1% sample.
+    ///
+    /// The rate must be the reciprocal of a whole number (`1`, `0.5`, `0.01`, ...). Every
+    /// update is then weighted by exactly `1 / rate`, so estimates and [`total`](CountMinSketch::total) directly approximate full-population
+    /// counts: one `increment` at rate `0.01` adds 100. The error bound `epsilon * total` is
+    /// scaled the same way, on top of the sampling error itself. The rate is not serialized.
+    pub fn sampling_rate(mut self, rate: f64) -> Self {
+        self.sampling_rate = Some(rate);
+        self
+    }
+
+    /// Builds the sketch.
+    ///
+    /// # Errors
+    /// Returns a [`BuildError`] if a dimension is set together with its parameter, a dimension
+    /// is zero, `epsilon` or `delta` is not strictly between 0 and 1, the sampling rate is not
+    /// in `(0, 1]` or not the reciprocal of a whole number, or the table would not be addressable.
+    pub fn build(&self) -> Result<CountMinSketch, BuildError> {
+        let width = match (self.width, self.epsilon) {
+            (Some(_), Some(_)) => return Err(BuildError::Conflict { dimension: "width", parameter: "epsilon" }),
+            (Some(width), None) => width,
+            (None, epsilon) => {
+                let epsilon = check_range("epsilon", epsilon.unwrap_or(DEFAULT_EPSILON))?;
+                (std::f64::consts::E / epsilon).ceil() as usize
+            }
+        };
+        let depth = match (self.depth, self.delta) {
+            (Some(_), Some(_)) => return Err(BuildError::Conflict { dimension: "depth", parameter: "delta" }),
+            (Some(depth), None) => depth,
+            (None, delta) => (1.0 / check_range("delta", delta.unwrap_or(DEFAULT_DELTA))?).ln().ceil() as usize,
+        };
+
+        let width = NonZeroUsize::new(width).ok_or(BuildError::ZeroDimension("width"))?;
+        let depth = NonZeroUsize::new(depth).ok_or(BuildError::ZeroDimension("depth"))?;
+        // No allocation may exceed `isize::MAX` bytes
+        let bytes = width
+            .checked_next_power_of_two()
+            .zip(depth.checked_next_power_of_two())
+            .and_then(|(w, d)| w.checked_mul(d))
+            .and_then(|cells| cells.get().checked_mul(size_of::<u64>()));
+        if bytes.is_none_or(|bytes| bytes > isize::MAX as usize) {
+            return Err(BuildError::TooLarge);
+        }
+        let sample_weight = match self.sampling_rate {
+            Some(rate) if rate > 0. && rate <= 1. => {
+                // Counters are integers: any other weight would bias every estimate
+                let weight = (1.0 / rate).round();
+                if (weight * rate - 1.0).abs() > 1e-9 {
+                    return Err(BuildError::InexactSamplingRate(rate));
+                }
+                weight as u64
+            }
+            Some(rate) => return Err(BuildError::OutOfRange { parameter: "sampling_rate", value: rate }),
+            None => 1,
+        };
+
+        let mut sketch = CountMinSketch::with_seeds(width, depth, self.seeds.unwrap_or(CountMinSketch::DEFAULT_SEEDS));
+        sketch.sample_weight = sample_weight;
+        Ok(sketch)
+    }
+}
+
+fn check_range(parameter: &'static str, value: f64) -> Result<f64, BuildError> {
+    if value > 0. && value < 1. {
+        Ok(value)
+    } else {
+        Err(BuildError::OutOfRange { parameter, value })
+    }
+}
+
+impl CountMinSketch {
+    /// Returns a [`CountMinSketchBuilder`] with nothing set.
+    pub fn builder() -> CountMinSketchBuilder {
+        CountMinSketchBuilder::new()
+    }
+}
+
+/// A sketch with `epsilon = 0.001` and `delta = 0.01` (4096 x 8), like `CountMinSketch::builder().build()`.
+impl Default for CountMinSketch {
+    fn default() -> Self {
+        Self::with_params(DEFAULT_EPSILON, DEFAULT_DELTA)
+    }
+}
@@ -0,0 +1,289 @@
+use std::io::{self, Read, Write};
+
+use crate::{CountMinSketch, HashAlgo, SketchError};
+
+/// The fixed-size header that starts every serialized sketch.
+///
+/// The header is `#[repr(C)]` with no padding, and every field is written little-endian. On
+/// little-endian targets the first [`SketchHeader::LEN`] bytes of a
+/// [`to_bytes`](CountMinSketch::to_bytes) buffer are therefore exactly this struct, so
+/// C consumers can read them directly. The `width * depth` counters follow as
+/// little-endian `u64`s, row by row.
+///
+/// `hash_algo` identifies the [`HashAlgo`]: 0 for `AHash`, 1 for `FxHash`, 2 for `SipHash`.
+/// `row_offset` is the first row of the original sketch held by a
+/// [`split_rows`](CountMinSketch::split_rows) half, 0 otherwise.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SketchHeader {
+    pub magic: u32,
+    pub version: u32,
+    pub width: u64,
+    pub depth: u64,
+    pub seeds: [u64; 4],
+    pub hash_algo: u64,
+    pub row_offset: u64,
+}
+
+impl SketchHeader {
+    /// Identifies a serialized sketch: the bytes `CMSK` read as a little-endian `u32`.
+    pub const MAGIC: u32 = u32::from_le_bytes(*b"CMSK");
+    /// Bumped on every layout change; buffers with another version are rejected.
+    pub const VERSION: u32 = 3;
+    /// Size of the encoded header in bytes.
+    pub const LEN: usize = std::mem::size_of::<SketchHeader>();
+
+    fn of(sketch: &CountMinSketch) -> Self {
+        Self {
+            magic: Self::MAGIC,
+            version: Self::VERSION,
+            width: sketch.width as u64,
+            depth: sketch.depth as u64,
+            seeds: sketch.seeds,
+            hash_algo: sketch.hash_algo().id(),
+            row_offset: sketch.row_offset as u64,
+        }
+    }
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.magic.to_le_bytes());
+        out.extend_from_slice(&self.version.to_le_bytes());
+        out.extend_from_slice(&self.width.to_le_bytes());
+        out.extend_from_slice(&self.depth.to_le_bytes());
+        for seed in self.seeds {
+            out.extend_from_slice(&seed.to_le_bytes());
+        }
+        out.extend_from_slice(&self.hash_algo.to_le_bytes());
+        out.extend_from_slice(&self.row_offset.to_le_bytes());
+    }
+
+    fn decode(bytes: &[u8; Self::LEN]) -> Self {
+        let mut words = cells(&bytes[8..]);
+        Self {
+            magic: u32::from_le_bytes(bytes[0..4].try_into().unwrap()),
+            version: u32::from_le_bytes(bytes[4..8].try_into().unwrap()),
+            width: words.next().unwrap(),
+            depth: words.next().unwrap(),
+            seeds: std::array::from_fn(|_| words.next().unwrap()),
+            hash_algo: words.next().unwrap(),
+            row_offset: words.next().unwrap(),
+        }
+    }
+}
+
+// magic (4) + version (4) + width (8) + depth (8) + seeds (4 * 8) + hash_algo (8) + row_offset (8),
+// without padding
+const _: () = assert!(SketchHeader::LEN == 72);
+
+impl CountMinSketch {
+    /// Serializes the full sketch state into a versioned little-endian byte buffer.
+    ///
+    /// The layout is a [`SketchHeader`] (magic, version, width, depth, the four hash seeds,
+    /// the hash algorithm, the row offset) followed by the `width * depth` counters, so [`from_bytes`](Self::from_bytes)
+    /// rebuilds a sketch that hashes identically to this one.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(SketchHeader::LEN + self.table.len() * 8);
+        SketchHeader::of(self).encode(&mut out);
+        for cell in self.table.iter() {
+            out.extend_from_slice(&cell.to_le_bytes());
+        }
+        out
+    }
+
+    /// Deserializes a sketch produced by [`to_bytes`](Self::to_bytes).
+    ///
+    /// # Errors
+    /// Returns an error if the buffer is truncated, was written by another format version,
+    /// describes invalid dimensions or an unknown hash algorithm, or its table length does not
+    /// match `width * depth`.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, SketchError> {
+        let (header, body) = parse(data)?;
+        let table = cells(body).collect();
+        Ok(Self::from_parts(header.width as usize, header.depth as usize, header.row_offset as usize, header.seeds, header.algo(), table))
+    }
+
+    /// Streams the [`to_bytes`](Self::to_bytes) encoding into `writer`, in fixed-size chunks
+    /// rather than one buffer holding the whole table.
+    ///
+    /// # Errors
+    /// Returns any I/O error from `writer`.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let mut buf = Vec::with_capacity(8 * 1024);
+        SketchHeader::of(self).encode(&mut buf);
+        writer.write_all(&buf)?;
+        for chunk in self.table.chunks(1024) {
+            buf.clear();
+            for cell in chunk {
+                buf.extend_from_slice(&cell.to_le_bytes());
+            }
+            writer.write_all(&buf)?;
+        }
+        Ok(())
+    }
+
+    /// Reads a sketch written by [`write_to`](Self::write_to) or [`to_bytes`](Self::to_bytes)
+    /// from a stream, consuming exactly the header and the table.
+    ///
+    /// # Errors
+    /// Returns an `InvalidData` error if the header is invalid (see [`from_bytes`](Self::from_bytes)),
+    /// and any I/O error from `reader`, including `UnexpectedEof` if the stream is truncated.
+    pub fn read_from<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let header = SketchHeader::read(reader)?;
+        let (width, depth) = (header.width as usize, header.depth as usize);
+        // The header is untrusted: grow the table as counters arrive rather than sizing it up
+        // front, so a truncated stream with huge dimensions fails instead of exhausting memory
+        let mut table = Vec::new();
+        read_cells(reader, width * depth, |_, cell| table.push(cell))?;
+        Ok(Self::from_parts(width, depth, header.row_offset as usize, header.seeds, header.algo(), table.into_boxed_slice()))
+    }
+
+    /// Merges a sketch serialized with [`to_bytes`](Self::to_bytes) into this one.
+    ///
+    /// Equivalent to `self.merge(&CountMinSketch::from_bytes(bytes)?)`, but the counters are
+    /// streamed straight from the buffer into a saturating per-cell merge, without
+    /// allocating a second table.
+    ///
+    /// # Errors
+    /// Returns an error if the buffer is invalid (see [`from_bytes`](Self::from_bytes)), or if
+    /// the serialized sketch has different dimensions, hash seeds or hash algorithm.
+    pub fn merge_from_bytes(&mut self, bytes: &[u8]) -> Result<(), SketchError> {
+        let (header, body) = parse(bytes)?;
+        if header.width != self.width as u64 || header.depth != self.depth as u64 || header.row_offset != self.row_offset as u64 {
+            return Err(SketchError::IncompatibleDimensions {
+                left: (self.width, self.depth),
+                right: (header.width as usize, header.depth as usize),
+            });
+        }
+        if header.seeds != self.seeds {
+            return Err(SketchError::IncompatibleSeeds);
+        }
+        if header.algo() != self.hash_algo() {
+            return Err(SketchError::IncompatibleHashAlgorithm);
+        }
+
+        let mut other_total = 0u64;
+        for (i, (a, b)) in self.table.iter_mut().zip(cells(body)).enumerate() {
+            if i < self.width {
+                other_total = other_total.saturating_add(b);
+            }
+            *a = a.saturating_add(b);
+        }
+        self.total = self.total.saturating_add(other_total);
+        Ok(())
+    }
+
+    /// Reduces a set of serialized shards, read from streams, into one merged sketch.
+    ///
+    /// The first stream initializes the result and every following one is merged into it
+    /// as it is read, like [`merge_from_bytes`](Self::merge_from_bytes): only the result
+    /// table is ever held in memory.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if `readers` is empty, an `InvalidData` error if a shard
+    /// is malformed or incompatible with the first one, and any I/O error from the streams.
+    pub fn reduce<R: Read, I: IntoIterator<Item = R>>(readers: I) -> io::Result<CountMinSketch> {
+        let mut readers = readers.into_iter();
+        let mut first = readers
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, SketchError::Empty))?;
+
+        let mut acc = Self::read_from(&mut first)?;
+        let width = acc.width;
+
+        for mut reader in readers {
+            let header = SketchHeader::read(&mut reader)?;
+            if header.width != acc.width as u64 || header.depth != acc.depth as u64 || header.row_offset != acc.row_offset as u64 {
+                return Err(invalid_data(SketchError::IncompatibleDimensions {
+                    left: (acc.width, acc.depth),
+                    right: (header.width as usize, header.depth as usize),
+                }));
+            }
+            if header.seeds != acc.seeds {
+                return Err(invalid_data(SketchError::IncompatibleSeeds));
+            }
+            if header.algo() != acc.hash_algo() {
+                return Err(invalid_data(SketchError::IncompatibleHashAlgorithm));
+            }
+            let mut other_total = 0u64;
+            read_cells(&mut reader, acc.table.len(), |i, cell| {
+                if i < width {
+                    other_total = other_total.saturating_add(cell);
+                }
+                acc.table[i] = acc.table[i].saturating_add(cell);
+            })?;
+            acc.total = acc.total.saturating_add(other_total);
+        }
+        Ok(acc)
+    }
+}
+
+/// Validates a serialized sketch, returning its header and the counter section.
+fn parse(data: &[u8]) -> Result<(SketchHeader, &[u8]), SketchError> {
+    let (header, body) = data.split_first_chunk::<{ SketchHeader::LEN }>().ok_or(SketchError::TruncatedHeader)?;
+    let header = SketchHeader::decode(header);
+    let cell_count = header.cell_count()?;
+    if body.len() / 8 != cell_count || body.len() % 8 != 0 {
+        return Err(SketchError::TableLengthMismatch);
+    }
+    Ok((header, body))
+}
+
+impl SketchHeader {
+    /// Checks magic, version, hash algorithm and dimensions, returning the number of counters that follow.
+    pub(crate) fn cell_count(&self) -> Result<usize, SketchError> {
+        if self.magic != Self::MAGIC {
+            return Err(SketchError::InvalidMagic);
+        }
+        if self.version != Self::VERSION {
+            return Err(SketchError::UnsupportedVersion);
+        }
+        if HashAlgo::from_id(self.hash_algo).is_none() {
+            return Err(SketchError::UnsupportedHashAlgorithm);
+        }
+        let width = usize::try_from(self.width).map_err(|_| SketchError::InvalidDimensions)?;
+        let depth = usize::try_from(self.depth).map_err(|_| SketchError::InvalidDimensions)?;
+        usize::try_from(self.row_offset).map_err(|_| SketchError::InvalidDimensions)?;
+        if !width.is_power_of_two() || depth == 0 {
+            return Err(SketchError::InvalidDimensions);
+        }
+        width.checked_mul(depth).ok_or(SketchError::InvalidDimensions)
+    }
+
+    /// Returns the hash algorithm of a header already checked by `cell_count`.
+    pub(crate) fn algo(&self) -> HashAlgo {
+        HashAlgo::from_id(self.hash_algo).expect("validated header")
+    }
+
+    /// Reads and validates a header from a stream.
+    fn read<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let mut bytes = [0u8; Self::LEN];
+        reader.read_exact(&mut bytes)?;
+        let header = Self::decode(&bytes);
+        header.cell_count().map_err(invalid_data)?;
+        Ok(header)
+    }
+}
+
+/// Streams `count` little-endian counters from `reader` in fixed-size chunks, without buffering the table.
+fn read_cells<R: Read, F: FnMut(usize, u64)>(reader: &mut R, count: usize, mut f: F) -> io::Result<()> {
+    let mut buf = [0u8; 8 * 1024];
+    let mut index = 0;
+    while index < count {
+        let n = (count - index).min(buf.len() / 8);
+        reader.read_exact(&mut buf[..n * 8])?;
+        for cell in cells(&buf[..n * 8]) {
+            f(index, cell);
+            index += 1;
+        }
+    }
+    Ok(())
+}
+
+fn invalid_data(err: SketchError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+/// Decodes a run of little-endian `u64` words.
+fn cells(bytes: &[u8]) -> impl Iterator<Item = u64> + '_ {
+    bytes.chunks_exact(8).map(|c| u64::from_le_bytes(c.try_into().unwrap()))
+}
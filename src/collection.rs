@@ -0,0 +1,119 @@
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{CountMinSketch, HashAlgo, SketchError};
+
+/// A set of sketches sharing one configuration, serialized with that configuration stored once.
+///
+/// Serializing a `Vec` of sketches would repeat width, depth, seeds and hash algorithm for
+/// every member. A collection writes them once, followed by one counter table per sketch.
+/// Every member must match the configuration of the first one, which is checked on `push`.
+/// Enabled by the `serde` feature.
+#[derive(Default)]
+pub struct SketchCollection {
+    sketches: Vec<CountMinSketch>,
+}
+
+impl SketchCollection {
+    /// Creates an empty collection; the first pushed sketch fixes the shared configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a sketch to the collection.
+    ///
+    /// # Errors
+    /// Returns an error if the sketch's dimensions, seeds or hash algorithm differ from
+    /// those of the collection's members.
+    pub fn push(&mut self, sketch: CountMinSketch) -> Result<(), SketchError> {
+        if let Some(first) = self.sketches.first() {
+            first.check_compatible(&sketch)?;
+        }
+        self.sketches.push(sketch);
+        Ok(())
+    }
+
+    /// Returns the number of sketches in the collection.
+    pub fn len(&self) -> usize {
+        self.sketches.len()
+    }
+
+    /// Returns `true` if the collection holds no sketch.
+    pub fn is_empty(&self) -> bool {
+        self.sketches.is_empty()
+    }
+
+    /// Returns the sketch at `index`, if any.
+    pub fn get(&self, index: usize) -> Option<&CountMinSketch> {
+        self.sketches.get(index)
+    }
+
+    /// Iterates over the sketches in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = &CountMinSketch> {
+        self.sketches.iter()
+    }
+
+    /// Consumes the collection, returning its sketches.
+    pub fn into_inner(self) -> Vec<CountMinSketch> {
+        self.sketches
+    }
+}
+
+/// Wire form of a collection. An empty collection is written with a zeroed configuration.
+#[derive(Serialize, Deserialize)]
+struct CollectionRepr<T> {
+    width: u64,
+    depth: u64,
+    row_offset: u64,
+    seeds: [u64; 4],
+    hash_algo: u64,
+    tables: Vec<T>,
+}
+
+impl Serialize for SketchCollection {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let tables: Vec<&[u64]> = self.sketches.iter().map(|s| &s.table[..]).collect();
+        let repr = match self.sketches.first() {
+            Some(first) => CollectionRepr {
+                width: first.width as u64,
+                depth: first.depth as u64,
+                row_offset: first.row_offset as u64,
+                seeds: first.seeds,
+                hash_algo: first.hash_algo().id(),
+                tables,
+            },
+            None => CollectionRepr { width: 0, depth: 0, row_offset: 0, seeds: [0; 4], hash_algo: 0, tables },
+        };
+        repr.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SketchCollection {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = CollectionRepr::<Vec<u64>>::deserialize(deserializer)?;
+        if repr.tables.is_empty() {
+            return Ok(Self::new());
+        }
+
+        let width = usize::try_from(repr.width).map_err(|_| D::Error::custom(SketchError::InvalidDimensions))?;
+        let depth = usize::try_from(repr.depth).map_err(|_| D::Error::custom(SketchError::InvalidDimensions))?;
+        let row_offset = usize::try_from(repr.row_offset).map_err(|_| D::Error::custom(SketchError::InvalidDimensions))?;
+        if !width.is_power_of_two() || depth == 0 {
+            return Err(D::Error::custom(SketchError::InvalidDimensions));
+        }
+        let cell_count = width.checked_mul(depth).ok_or_else(|| D::Error::custom(SketchError::InvalidDimensions))?;
+        let algo = HashAlgo::from_id(repr.hash_algo).ok_or_else(|| D::Error::custom(SketchError::UnsupportedHashAlgorithm))?;
+
+        let sketches = repr
+            .tables
+            .into_iter()
+            .map(|table| {
+                if table.len() != cell_count {
+                    return Err(D::Error::custom(SketchError::TableLengthMismatch));
+                }
+                Ok(CountMinSketch::from_parts(width, depth, row_offset, repr.seeds, algo, table.into_boxed_slice()))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { sketches })
+    }
+}
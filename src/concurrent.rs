@@ -0,0 +1,128 @@
+use std::hash::Hash;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock;
+
+use crate::hashing::{HashAlgo, SketchHasher};
+use crate::CountMinSketch;
+
+/// How a [`ConcurrentCountMinSketch`] orders the cell updates of one key against readers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Consistency {
+    /// Each cell is updated on its own, lock-free. A concurrent read may see some of a key's
+    /// cells already incremented and others not yet.
+    #[default]
+    Relaxed,
+    /// All of a key's cells are updated under a short per-shard lock, which reads of keys in
+    /// the same shard also take, so they observe either none or all of an increment.
+    RowConsistent,
+}
+
+/// A Count-Min Sketch with atomic cells, updatable through a shared reference.
+///
+/// `increment` takes `&self` and adds to each cell with a `Relaxed` `fetch_add`, so one
+/// sketch can be shared by a pool of ingesting threads (e.g. behind an `Arc`) without a
+/// lock. Cells wrap around instead of saturating, which only matters past `u64::MAX`
+/// increments of a cell. Keys are hashed exactly like a [`CountMinSketch`] with the same
+/// dimensions and seeds, and [`snapshot`](Self::snapshot) converts the counts to one.
+///
+/// With [`Consistency::RowConsistent`], keys are spread over a fixed set of locks by hash; the
+/// guarantee covers a key's own increments, while cells shared with colliding keys of other
+/// shards can still change mid-read.
+pub struct ConcurrentCountMinSketch {
+    width: usize,
+    width_mask: usize,
+    depth: usize,
+    table: Box<[AtomicU64]>,
+    hasher: SketchHasher,
+    seeds: [u64; 4],
+    /// One lock per shard with `RowConsistent`, none with `Relaxed`.
+    shards: Box<[RwLock<()>]>,
+}
+
+impl ConcurrentCountMinSketch {
+    /// Creates a sketch with explicit `width` and `depth`, see [`CountMinSketch::new`].
+    pub fn new(width: NonZeroUsize, depth: NonZeroUsize) -> Self {
+        Self::with_seeds(width, depth, CountMinSketch::DEFAULT_SEEDS)
+    }
+
+    /// Creates a sketch with explicit dimensions and custom hash seeds, see [`CountMinSketch::with_seeds`].
+    pub fn with_seeds(width: NonZeroUsize, depth: NonZeroUsize, seeds: [u64; 4]) -> Self {
+        Self::with_consistency(width, depth, seeds, Consistency::Relaxed)
+    }
+
+    /// Creates a sketch with explicit dimensions and seeds whose updates follow `consistency`.
+    pub fn with_consistency(width: NonZeroUsize, depth: NonZeroUsize, seeds: [u64; 4], consistency: Consistency) -> Self {
+        let w = width.get().next_power_of_two();
+        let d = depth.get().next_power_of_two();
+        Self {
+            width: w,
+            width_mask: w - 1,
+            depth: d,
+            table: (0..w * d).map(|_| AtomicU64::new(0)).collect(),
+            hasher: SketchHasher::new(HashAlgo::AHash, seeds),
+            seeds,
+            shards: match consistency {
+                Consistency::Relaxed => Box::new([]),
+                Consistency::RowConsistent => (0..Self::SHARDS).map(|_| RwLock::new(())).collect(),
+            },
+        }
+    }
+
+    /// Number of locks keys are spread over with `RowConsistent`.
+    const SHARDS: usize = 64;
+
+    /// Returns the update ordering chosen at construction.
+    pub fn consistency(&self) -> Consistency {
+        if self.shards.is_empty() { Consistency::Relaxed } else { Consistency::RowConsistent }
+    }
+
+    /// The lock guarding the key with primary hash `h1`, if any. The high bits pick the shard,
+    /// so keys of one shard still spread over all columns.
+    #[inline]
+    fn shard(&self, h1: u64) -> Option<&RwLock<()>> {
+        self.shards.get((h1 >> 58) as usize)
+    }
+
+    /// Increments the frequency count for the given item.
+    #[inline]
+    pub fn increment<T: Hash + ?Sized>(&self, item: &T) {
+        self.increment_by(item, 1);
+    }
+
+    /// Adds `count` to the frequency of the given item, wrapping on overflow.
+    pub fn increment_by<T: Hash + ?Sized>(&self, item: &T, count: u64) {
+        let h1 = self.hasher.hash_one(item);
+        // The lock only guards the atomics' visibility, so a poisoned one is still usable
+        let _guard = self.shard(h1).map(|lock| lock.write().unwrap_or_else(|e| e.into_inner()));
+        <CountMinSketch>::calculate_indices(h1, 0, self.depth, self.width, self.width_mask, |idx| {
+            self.table[idx].fetch_add(count, Ordering::Relaxed);
+        });
+    }
+
+    /// Estimates the frequency count of the given item.
+    ///
+    /// Concurrent increments of the item may or may not be reflected yet.
+    pub fn estimate<T: Hash + ?Sized>(&self, item: &T) -> u64 {
+        self.cells(item).into_iter().min().unwrap_or(0)
+    }
+
+    /// Returns the item's counter in each row, read under the sketch's [`Consistency`].
+    pub fn cells<T: Hash + ?Sized>(&self, item: &T) -> Vec<u64> {
+        let h1 = self.hasher.hash_one(item);
+        let _guard = self.shard(h1).map(|lock| lock.read().unwrap_or_else(|e| e.into_inner()));
+        let mut cells = Vec::with_capacity(self.depth);
+        <CountMinSketch>::calculate_indices(h1, 0, self.depth, self.width, self.width_mask, |idx| {
+            cells.push(self.table[idx].load(Ordering::Relaxed));
+        });
+        cells
+    }
+
+    /// Copies the current counts into a regular [`CountMinSketch`] with the same hashing.
+    ///
+    /// Cells are read one by one, so increments racing with the copy may be partially included.
+    pub fn snapshot(&self) -> CountMinSketch {
+        let table = self.table.iter().map(|cell| cell.load(Ordering::Relaxed)).collect();
+        CountMinSketch::from_parts(self.width, self.depth, 0, self.seeds, HashAlgo::AHash, table)
+    }
+}
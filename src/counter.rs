@@ -0,0 +1,61 @@
+mod private {
+    pub trait Sealed {}
+}
+
+/// The integer type of a sketch's cells, implemented for `u8`, `u16`, `u32` and `u64`.
+///
+/// Narrower counters divide the table memory accordingly, e.g. `CountMinSketch<u16>` takes a
+/// quarter of the default `u64` table. Counters saturate at the type's maximum, so pick one
+/// wide enough for the largest count expected. This trait is sealed.
+pub trait Counter: Copy + Ord + Send + Sync + 'static + private::Sealed {
+    /// The value of an empty cell.
+    const ZERO: Self;
+
+    /// The value a cell saturates at.
+    const MAX: Self;
+
+    /// Adds two counters, saturating at the type's maximum.
+    fn saturating_add(self, other: Self) -> Self;
+
+    /// Subtracts two counters, saturating at zero.
+    fn saturating_sub(self, other: Self) -> Self;
+
+    /// Adds an increment, saturating at the type's maximum.
+    fn saturating_add_count(self, count: u64) -> Self;
+
+    /// Widens the counter to `u64`.
+    fn to_u64(self) -> u64;
+}
+
+macro_rules! impl_counter {
+    ($($t:ty),*) => {$(
+        impl private::Sealed for $t {}
+
+        impl Counter for $t {
+            const ZERO: Self = 0;
+            const MAX: Self = <$t>::MAX;
+
+            #[inline(always)]
+            fn saturating_add(self, other: Self) -> Self {
+                <$t>::saturating_add(self, other)
+            }
+
+            #[inline(always)]
+            fn saturating_sub(self, other: Self) -> Self {
+                <$t>::saturating_sub(self, other)
+            }
+
+            #[inline(always)]
+            fn saturating_add_count(self, count: u64) -> Self {
+                <$t>::try_from(count).map_or(<$t>::MAX, |count| <$t>::saturating_add(self, count))
+            }
+
+            #[inline(always)]
+            fn to_u64(self) -> u64 {
+                self as u64
+            }
+        }
+    )*};
+}
+
+impl_counter!(u8, u16, u32, u64);
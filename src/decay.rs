@@ -0,0 +1,154 @@
+use std::hash::Hash;
+use std::num::NonZeroUsize;
+use std::time::{Duration, Instant};
+
+use crate::CountMinSketch;
+
+/// A source of the current time, injectable into [`TimedDecaySketch`] for tests.
+pub trait Clock {
+    /// Returns the current instant.
+    fn now(&self) -> Instant;
+}
+
+/// The wall clock, [`Instant::now`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A [`CountMinSketch`] whose counts halve every `half_life` of elapsed time.
+///
+/// Instead of a timer thread calling [`decay`](CountMinSketch::decay), the sketch remembers
+/// when it last decayed and catches up lazily: `increment` first applies one halving per
+/// full half-life elapsed since then, and `estimate` reports the count as if it had. Decay
+/// happens in whole half-life steps; the remainder carries over to the next catch-up.
+pub struct TimedDecaySketch<C: Clock = SystemClock> {
+    sketch: CountMinSketch,
+    half_life: Duration,
+    last_decay: Instant,
+    clock: C,
+}
+
+impl TimedDecaySketch {
+    /// Creates a sketch with explicit `width` and `depth` decaying on the wall clock.
+    ///
+    /// Panics if `half_life` is zero.
+    pub fn new(width: NonZeroUsize, depth: NonZeroUsize, half_life: Duration) -> Self {
+        Self::with_clock(CountMinSketch::new(width, depth), half_life, SystemClock)
+    }
+}
+
+impl<C: Clock> TimedDecaySketch<C> {
+    /// Wraps an existing sketch, decaying on `clock` from now on.
+    ///
+    /// Panics if `half_life` is zero.
+    pub fn with_clock(sketch: CountMinSketch, half_life: Duration, clock: C) -> Self {
+        assert!(!half_life.is_zero(), "half_life must be positive.");
+        let last_decay = clock.now();
+        Self { sketch, half_life, last_decay, clock }
+    }
+
+    /// Applies pending decay, then increments the frequency count for the given key.
+    pub fn increment<T: Hash + ?Sized>(&mut self, key: &T) {
+        self.increment_by(key, 1);
+    }
+
+    /// Applies pending decay, then adds `count` to the frequency of the given key.
+    pub fn increment_by<T: Hash + ?Sized>(&mut self, key: &T, count: u64) {
+        let halvings = self.pending_halvings();
+        if halvings > 0 {
+            self.sketch.decay(Self::factor(halvings));
+            // Keep the partial half-life for the next catch-up
+            self.last_decay = match self.half_life.checked_mul(halvings) {
+                Some(step) => self.last_decay + step,
+                None => self.clock.now(),
+            };
+        }
+        self.sketch.increment_by(key, count);
+    }
+
+    /// Estimates the decayed frequency count of the given key at the current time.
+    pub fn estimate<T: Hash + ?Sized>(&self, key: &T) -> u64 {
+        let estimate = self.sketch.estimate(key);
+        match self.pending_halvings() {
+            0 => estimate,
+            // Flooring the minimum equals the minimum of the floored cells
+            halvings => (estimate as f64 * Self::factor(halvings)) as u64,
+        }
+    }
+
+    /// Returns the underlying sketch, without the decay still pending.
+    pub fn as_sketch(&self) -> &CountMinSketch {
+        &self.sketch
+    }
+
+    /// Number of whole half-lives elapsed since the last applied decay.
+    fn pending_halvings(&self) -> u32 {
+        let elapsed = self.clock.now().saturating_duration_since(self.last_decay);
+        (elapsed.as_nanos() / self.half_life.as_nanos()).min(u32::MAX as u128) as u32
+    }
+
+    fn factor(halvings: u32) -> f64 {
+        0.5f64.powi(halvings.min(i32::MAX as u32) as i32)
+    }
+}
+
+/// A [`CountMinSketch`] that calls [`decay`](CountMinSketch::decay) every `n` increments.
+///
+/// Counts age by update volume rather than time: each `increment` or `increment_by` call
+/// counts as one increment, and the `n`-th one is followed by `decay(factor)`.
+pub struct DecayEvery {
+    sketch: CountMinSketch,
+    n: usize,
+    factor: f64,
+    since_decay: usize,
+}
+
+impl DecayEvery {
+    /// Wraps an existing sketch, decaying it by `factor` after every `n` increments.
+    ///
+    /// Panics if `factor` is not within `0.0..=1.0`.
+    pub fn new(sketch: CountMinSketch, n: NonZeroUsize, factor: f64) -> Self {
+        assert!((0.0..=1.0).contains(&factor), "factor must be between 0 and 1 included.");
+        Self { sketch, n: n.get(), factor, since_decay: 0 }
+    }
+
+    /// Increments the frequency count for the given key, decaying if `n` increments were reached.
+    pub fn increment<T: Hash + ?Sized>(&mut self, key: &T) {
+        self.increment_by(key, 1);
+    }
+
+    /// Adds `count` to the frequency of the given key, decaying if `n` increments were reached.
+    pub fn increment_by<T: Hash + ?Sized>(&mut self, key: &T, count: u64) {
+        self.sketch.increment_by(key, count);
+        self.since_decay += 1;
+        if self.since_decay == self.n {
+            self.sketch.decay(self.factor);
+            self.since_decay = 0;
+        }
+    }
+
+    /// Estimates the decayed frequency count of the given key.
+    pub fn estimate<T: Hash + ?Sized>(&self, key: &T) -> u64 {
+        self.sketch.estimate(key)
+    }
+
+    /// Returns the number of increments between two decays.
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    /// Returns the factor applied at every decay.
+    pub fn factor(&self) -> f64 {
+        self.factor
+    }
+
+    /// Returns the underlying sketch.
+    pub fn as_sketch(&self) -> &CountMinSketch {
+        &self.sketch
+    }
+}
@@ -0,0 +1,139 @@
+use std::fmt;
+
+/// Returned by the checked update paths when applying a weight would push
+/// at least one counter past `u64::MAX`.
+///
+/// The sketch is left untouched when this error is returned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SaturationError;
+
+impl fmt::Display for SaturationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Counter would overflow u64::MAX")
+    }
+}
+
+impl std::error::Error for SaturationError {}
+
+/// A suspicious sketch geometry flagged by [`CountMinSketch::validate_dimensions`](crate::CountMinSketch::validate_dimensions).
+///
+/// Depth buys confidence logarithmically while width buys accuracy linearly, so a useful
+/// sketch is almost always wide and shallow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DimensionWarning {
+    /// More rows than columns: most of the memory is spent on confidence rather than accuracy.
+    DepthExceedsWidth { width: usize, depth: usize },
+    /// Depth above the sensible maximum, i.e. an error probability far below `e^-64`.
+    DepthTooLarge { depth: usize, max: usize },
+}
+
+impl fmt::Display for DimensionWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DepthExceedsWidth { width, depth } => {
+                write!(f, "Depth {} exceeds width {}", depth, width)
+            }
+            Self::DepthTooLarge { depth, max } => {
+                write!(f, "Depth {} exceeds the sensible maximum of {}", depth, max)
+            }
+        }
+    }
+}
+
+/// Returned by [`CountMinSketchBuilder::build`](crate::CountMinSketchBuilder::build) for an
+/// invalid or contradictory configuration.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BuildError {
+    /// An explicit dimension and the parameter it would be derived from were both set,
+    /// i.e. `width` with `epsilon`, or `depth` with `delta`.
+    Conflict { dimension: &'static str, parameter: &'static str },
+    /// `width` or `depth` was set to zero.
+    ZeroDimension(&'static str),
+    /// `epsilon` or `delta` is not strictly between 0 and 1, or the sampling rate is not in `(0, 1]`.
+    OutOfRange { parameter: &'static str, value: f64 },
+    /// The table would take more bytes than a single allocation can hold (`isize::MAX`).
+    TooLarge,
+    /// The sampling rate is not the reciprocal of a whole number, so no integer weight scales
+    /// sampled updates back without bias.
+    InexactSamplingRate(f64),
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Conflict { dimension, parameter } => {
+                write!(f, "Both {} and {} were set", dimension, parameter)
+            }
+            Self::ZeroDimension(dimension) => write!(f, "{} must be nonzero", dimension),
+            Self::OutOfRange { parameter, value } => {
+                write!(f, "{} is out of range, got {}", parameter, value)
+            }
+            Self::TooLarge => f.write_str("Table size exceeds the maximum allocation"),
+            Self::InexactSamplingRate(rate) => {
+                write!(f, "Sampling rate {} is not the reciprocal of a whole number", rate)
+            }
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+/// Returned when sketches cannot be combined or a serialized sketch cannot be decoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SketchError {
+    /// The sketches differ in `(width, depth)`, or cover different row ranges.
+    IncompatibleDimensions { left: (usize, usize), right: (usize, usize) },
+    /// The sketches hash keys with different seeds.
+    IncompatibleSeeds,
+    /// The sketches hash keys with different algorithms.
+    IncompatibleHashAlgorithm,
+    /// The row ranges to reassemble are not adjacent.
+    RowsNotAdjacent,
+    /// The split point is not strictly inside the sketch's rows.
+    InvalidSplitPoint,
+    /// An operation needing at least one sketch was given none.
+    Empty,
+    /// The buffer is shorter than a header.
+    TruncatedHeader,
+    /// The buffer does not start with the sketch magic number.
+    InvalidMagic,
+    /// The buffer was written by another format version.
+    UnsupportedVersion,
+    /// The buffer names an unknown hash algorithm.
+    UnsupportedHashAlgorithm,
+    /// The serialized dimensions are zero, not a power of two, or not addressable.
+    InvalidDimensions,
+    /// The serialized table length does not match `width * depth`.
+    TableLengthMismatch,
+    /// A statistical parameter, `epsilon` or `delta`, is not strictly between 0 and 1.
+    InvalidParameter(&'static str),
+    /// The table would take `bytes` bytes, more than the allowed limit.
+    TooLarge { bytes: u128 },
+}
+
+impl fmt::Display for SketchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IncompatibleDimensions { left, right } => {
+                write!(f, "Incompatible dimensions: {}x{} and {}x{}", left.0, left.1, right.0, right.1)
+            }
+            Self::IncompatibleSeeds => f.write_str("Incompatible seeds"),
+            Self::IncompatibleHashAlgorithm => f.write_str("Incompatible hash algorithm"),
+            Self::RowsNotAdjacent => f.write_str("Rows are not adjacent"),
+            Self::InvalidSplitPoint => f.write_str("Invalid split point"),
+            Self::Empty => f.write_str("No sketches given"),
+            Self::TruncatedHeader => f.write_str("Truncated header"),
+            Self::InvalidMagic => f.write_str("Invalid magic"),
+            Self::UnsupportedVersion => f.write_str("Unsupported format version"),
+            Self::UnsupportedHashAlgorithm => f.write_str("Unsupported hash algorithm"),
+            Self::InvalidDimensions => f.write_str("Invalid dimensions"),
+            Self::TableLengthMismatch => f.write_str("Table length mismatch"),
+            Self::InvalidParameter(parameter) => {
+                write!(f, "{} must be a positive between 0 and 1 excluded", parameter)
+            }
+            Self::TooLarge { bytes } => write!(f, "Table of {} bytes exceeds the limit", bytes),
+        }
+    }
+}
+
+impl std::error::Error for SketchError {}
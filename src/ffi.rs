@@ -0,0 +1,65 @@
+//! C ABI over an opaque `CountMinSketch` pointer, enabled by the `ffi` feature.
+//!
+//! Keys cross the boundary as `(pointer, length)` byte buffers and are hashed as a Rust
+//! `&[u8]`, so a Rust caller sharing the sketch must also count keys as byte slices.
+//! Persisted sketches can be exchanged through the [`SketchHeader`](crate::SketchHeader) layout.
+
+use std::num::NonZeroUsize;
+use std::slice;
+
+use crate::CountMinSketch;
+
+/// Creates a sketch of at least `width` x `depth` cells (both rounded up to powers of two).
+///
+/// Returns a null pointer if either dimension is zero. The sketch must be released with [`cms_free`].
+#[unsafe(no_mangle)]
+pub extern "C" fn cms_new(width: usize, depth: usize) -> *mut CountMinSketch {
+    match (NonZeroUsize::new(width), NonZeroUsize::new(depth)) {
+        (Some(w), Some(d)) => Box::into_raw(Box::new(CountMinSketch::new(w, d))),
+        _ => std::ptr::null_mut(),
+    }
+}
+
+/// Increments the count of the `len`-byte key at `key`. Does nothing if `sketch` is null.
+///
+/// # Safety
+/// `sketch` must be null or a live pointer returned by [`cms_new`], not used concurrently,
+/// and `key` must be valid for reads of `len` bytes (it may be dangling if `len` is 0).
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn cms_increment(sketch: *mut CountMinSketch, key: *const u8, len: usize) {
+    if let Some(sketch) = unsafe { sketch.as_mut() } {
+        sketch.increment(unsafe { key_bytes(key, len) });
+    }
+}
+
+/// Returns the estimated count of the `len`-byte key at `key`, or 0 if `sketch` is null.
+///
+/// # Safety
+/// `sketch` must be null or a live pointer returned by [`cms_new`], and `key` must be valid
+/// for reads of `len` bytes (it may be dangling if `len` is 0).
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn cms_estimate(sketch: *const CountMinSketch, key: *const u8, len: usize) -> u64 {
+    match unsafe { sketch.as_ref() } {
+        Some(sketch) => sketch.estimate(unsafe { key_bytes(key, len) }),
+        None => 0,
+    }
+}
+
+/// Releases a sketch created by [`cms_new`]. Does nothing if `sketch` is null.
+///
+/// # Safety
+/// `sketch` must be null or a pointer returned by [`cms_new`] that has not been freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn cms_free(sketch: *mut CountMinSketch) {
+    if !sketch.is_null() {
+        drop(unsafe { Box::from_raw(sketch) });
+    }
+}
+
+unsafe fn key_bytes<'a>(key: *const u8, len: usize) -> &'a [u8] {
+    if len == 0 {
+        &[]
+    } else {
+        unsafe { slice::from_raw_parts(key, len) }
+    }
+}
@@ -0,0 +1,263 @@
+use std::hash::{BuildHasher, Hash, Hasher};
+#[allow(deprecated)]
+use std::hash::SipHasher;
+
+use ahash::{AHasher, RandomState};
+
+/// The hash function a sketch maps keys with, chosen at construction.
+///
+/// * `AHash` (the default) is the fastest, but its output depends on whether the crate was
+///   compiled with the `aes` target feature (see the [`CountMinSketch`](crate::CountMinSketch)
+///   hash portability notes).
+/// * `FxHash` is a very cheap multiply-rotate hash, well suited to small integer keys. It is
+///   not resistant to adversarial keys.
+/// * `SipHash` (SipHash-2-4) is slower but keyed and robust against crafted collisions.
+///
+/// `FxHash` and `SipHash` feed every integer as fixed-width little-endian bytes (`usize` as
+/// `u64`), so they produce the same cells on every target and build configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum HashAlgo {
+    #[default]
+    AHash,
+    FxHash,
+    SipHash,
+}
+
+impl HashAlgo {
+    /// Stable identifier written to serialized sketches.
+    pub(crate) fn id(self) -> u64 {
+        match self {
+            HashAlgo::AHash => 0,
+            HashAlgo::FxHash => 1,
+            HashAlgo::SipHash => 2,
+        }
+    }
+
+    pub(crate) fn from_id(id: u64) -> Option<Self> {
+        match id {
+            0 => Some(HashAlgo::AHash),
+            1 => Some(HashAlgo::FxHash),
+            2 => Some(HashAlgo::SipHash),
+            _ => None,
+        }
+    }
+}
+
+/// Seeded hasher factory for one [`HashAlgo`], dispatched by enum rather than generics.
+///
+/// `AHash` uses all four seeds, `SipHash` keys on the first two and `FxHash` starts its state
+/// from the first.
+#[derive(Clone)]
+pub(crate) enum SketchHasher {
+    AHash(RandomState),
+    Fx(u64),
+    Sip(u64, u64),
+}
+
+impl SketchHasher {
+    pub(crate) fn new(algo: HashAlgo, seeds: [u64; 4]) -> Self {
+        match algo {
+            HashAlgo::AHash => SketchHasher::AHash(RandomState::with_seeds(seeds[0], seeds[1], seeds[2], seeds[3])),
+            HashAlgo::FxHash => SketchHasher::Fx(seeds[0]),
+            HashAlgo::SipHash => SketchHasher::Sip(seeds[0], seeds[1]),
+        }
+    }
+
+    pub(crate) fn algo(&self) -> HashAlgo {
+        match self {
+            SketchHasher::AHash(_) => HashAlgo::AHash,
+            SketchHasher::Fx(_) => HashAlgo::FxHash,
+            SketchHasher::Sip(..) => HashAlgo::SipHash,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn hash_one<T: Hash + ?Sized>(&self, item: &T) -> u64 {
+        match self {
+            // Keep the direct path for the default algorithm
+            SketchHasher::AHash(state) => state.hash_one(item),
+            _ => BuildHasher::hash_one(self, item),
+        }
+    }
+}
+
+impl BuildHasher for SketchHasher {
+    type Hasher = AnyHasher;
+
+    #[inline]
+    fn build_hasher(&self) -> AnyHasher {
+        match self {
+            SketchHasher::AHash(state) => AnyHasher::AHash(state.build_hasher()),
+            SketchHasher::Fx(seed) => AnyHasher::Fx(FxHasher { hash: *seed }),
+            #[allow(deprecated)]
+            SketchHasher::Sip(k0, k1) => AnyHasher::Sip(PortableSipHasher(SipHasher::new_with_keys(*k0, *k1))),
+        }
+    }
+}
+
+/// A running hash state of any supported algorithm.
+///
+/// Every `write_*` method is forwarded, so hashers with specialized integer paths (like
+/// `AHasher`) produce exactly the values they would produce on their own.
+#[derive(Clone)]
+pub(crate) enum AnyHasher {
+    AHash(AHasher),
+    Fx(FxHasher),
+    Sip(PortableSipHasher),
+}
+
+macro_rules! forward {
+    ($($method:ident($ty:ty)),* $(,)?) => {
+        $(
+            #[inline]
+            fn $method(&mut self, value: $ty) {
+                match self {
+                    AnyHasher::AHash(h) => h.$method(value),
+                    AnyHasher::Fx(h) => h.$method(value),
+                    AnyHasher::Sip(h) => h.$method(value),
+                }
+            }
+        )*
+    };
+}
+
+impl Hasher for AnyHasher {
+    #[inline]
+    fn finish(&self) -> u64 {
+        match self {
+            AnyHasher::AHash(h) => h.finish(),
+            AnyHasher::Fx(h) => h.finish(),
+            AnyHasher::Sip(h) => h.finish(),
+        }
+    }
+
+    forward!(
+        write(&[u8]),
+        write_u8(u8),
+        write_u16(u16),
+        write_u32(u32),
+        write_u64(u64),
+        write_u128(u128),
+        write_usize(usize),
+    );
+}
+
+/// The `FxHasher` of the Rust compiler, started from a seed.
+///
+/// Mixing happens in the high bits of the state, so `finish` rotates them down: bucket
+/// selection only looks at the low bits of the hash.
+#[derive(Clone)]
+pub(crate) struct FxHasher {
+    hash: u64,
+}
+
+impl FxHasher {
+    const K: u64 = 0x517c_c1b7_2722_0a95;
+
+    #[inline]
+    fn add_to_hash(&mut self, word: u64) {
+        self.hash = (self.hash.rotate_left(5) ^ word).wrapping_mul(Self::K);
+    }
+}
+
+impl Hasher for FxHasher {
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        let mut chunks = bytes.chunks_exact(8);
+        for chunk in &mut chunks {
+            self.add_to_hash(u64::from_le_bytes(chunk.try_into().unwrap()));
+        }
+        let rest = chunks.remainder();
+        if !rest.is_empty() {
+            let mut word = [0u8; 8];
+            word[..rest.len()].copy_from_slice(rest);
+            self.add_to_hash(u64::from_le_bytes(word));
+        }
+    }
+
+    #[inline]
+    fn write_u8(&mut self, i: u8) {
+        self.add_to_hash(i as u64);
+    }
+
+    #[inline]
+    fn write_u16(&mut self, i: u16) {
+        self.add_to_hash(i as u64);
+    }
+
+    #[inline]
+    fn write_u32(&mut self, i: u32) {
+        self.add_to_hash(i as u64);
+    }
+
+    #[inline]
+    fn write_u64(&mut self, i: u64) {
+        self.add_to_hash(i);
+    }
+
+    #[inline]
+    fn write_u128(&mut self, i: u128) {
+        self.add_to_hash(i as u64);
+        self.add_to_hash((i >> 64) as u64);
+    }
+
+    #[inline]
+    fn write_usize(&mut self, i: usize) {
+        self.add_to_hash(i as u64);
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        self.hash.rotate_left(26)
+    }
+}
+
+/// SipHash-2-4 with every integer fed as fixed-width little-endian bytes.
+///
+/// `std`'s `SipHasher` writes most integers in native byte order and `usize` at pointer
+/// width, so its output would differ between targets. Widening `usize` to `u64` keeps the
+/// hashes of 64-bit little-endian targets unchanged.
+#[derive(Clone)]
+pub(crate) struct PortableSipHasher(#[allow(deprecated)] SipHasher);
+
+impl Hasher for PortableSipHasher {
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.write(bytes);
+    }
+
+    #[inline]
+    fn write_u8(&mut self, i: u8) {
+        self.0.write(&[i]);
+    }
+
+    #[inline]
+    fn write_u16(&mut self, i: u16) {
+        self.0.write(&i.to_le_bytes());
+    }
+
+    #[inline]
+    fn write_u32(&mut self, i: u32) {
+        self.0.write(&i.to_le_bytes());
+    }
+
+    #[inline]
+    fn write_u64(&mut self, i: u64) {
+        self.0.write(&i.to_le_bytes());
+    }
+
+    #[inline]
+    fn write_u128(&mut self, i: u128) {
+        self.0.write(&i.to_le_bytes());
+    }
+
+    #[inline]
+    fn write_usize(&mut self, i: usize) {
+        self.0.write(&(i as u64).to_le_bytes());
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        self.0.finish()
+    }
+}
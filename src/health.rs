@@ -0,0 +1,109 @@
+use std::fmt;
+
+use crate::CountMinSketch;
+
+/// Share of nonzero cells above which [`SketchWarning::HighFillRatio`] is reported.
+const MAX_FILL_RATIO: f64 = 0.5;
+/// A row holding fewer nonzero cells than this share of the average row is reported as skewed.
+const MIN_ROW_FILL_SHARE: f64 = 0.5;
+
+/// How urgently a [`SketchWarning`] calls for action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Worth knowing, estimates are still within their guarantees.
+    Info,
+    /// Estimates are degrading: plan to resize or rotate the sketch.
+    Warning,
+    /// Estimates can no longer be trusted: rotate the sketch now.
+    Critical,
+}
+
+/// A problem found by [`CountMinSketch::health_check`].
+///
+/// The human-readable message is the `Display` output.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SketchWarning {
+    /// More than half of the cells are nonzero, so most keys collide with others.
+    HighFillRatio { ratio: f64 },
+    /// Some cells reached `u64::MAX` and stopped counting.
+    SaturatedCells { count: usize },
+    /// A single row gives no protection against collisions.
+    SingleRow,
+    /// A row has far fewer nonzero cells than the average row, hinting at a poorly spread hash.
+    SkewedRow { row: usize, nonzero: usize, mean: f64 },
+    /// The error bound `epsilon * total` reaches the largest counter, so no estimate stands out from the noise.
+    ErrorBoundExceeded { bound: u64, largest: u64 },
+}
+
+impl SketchWarning {
+    /// Returns how urgently the warning calls for action.
+    pub fn severity(&self) -> Severity {
+        match self {
+            Self::SaturatedCells { .. } => Severity::Critical,
+            Self::HighFillRatio { .. } | Self::SkewedRow { .. } | Self::ErrorBoundExceeded { .. } => Severity::Warning,
+            Self::SingleRow => Severity::Info,
+        }
+    }
+}
+
+impl fmt::Display for SketchWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::HighFillRatio { ratio } => {
+                write!(f, "{:.0}% of the cells are nonzero, consider a wider sketch", ratio * 100.0)
+            }
+            Self::SaturatedCells { count } => {
+                write!(f, "{} cells are saturated at u64::MAX", count)
+            }
+            Self::SingleRow => f.write_str("The sketch has a single row"),
+            Self::SkewedRow { row, nonzero, mean } => {
+                write!(f, "Row {} has {} nonzero cells against an average of {:.1}", row, nonzero, mean)
+            }
+            Self::ErrorBoundExceeded { bound, largest } => {
+                write!(f, "Error bound {} reaches the largest counter {}", bound, largest)
+            }
+        }
+    }
+}
+
+impl CountMinSketch {
+    /// Inspects the table and returns every problem found, most severe first.
+    ///
+    /// Checks for a fill ratio above 50%, saturated cells, a depth of one, rows with less than
+    /// half the average number of nonzero cells, and an error bound `actual_epsilon() * total`
+    /// reaching the largest counter. An empty result means the sketch is healthy. Meant for
+    /// control planes deciding when to rotate or resize a sketch.
+    pub fn health_check(&self) -> Vec<SketchWarning> {
+        let mut warnings = Vec::new();
+        let row_fill: Vec<usize> = self.table.chunks_exact(self.width).map(|row| row.iter().filter(|&&c| c > 0).count()).collect();
+        let nonzero: usize = row_fill.iter().sum();
+
+        let ratio = nonzero as f64 / self.table.len() as f64;
+        if ratio > MAX_FILL_RATIO {
+            warnings.push(SketchWarning::HighFillRatio { ratio });
+        }
+        let saturated = self.saturated_cells();
+        if saturated > 0 {
+            warnings.push(SketchWarning::SaturatedCells { count: saturated });
+        }
+        if self.depth == 1 {
+            warnings.push(SketchWarning::SingleRow);
+        }
+        let mean = nonzero as f64 / self.depth as f64;
+        for (row, &fill) in row_fill.iter().enumerate() {
+            if (fill as f64) < mean * MIN_ROW_FILL_SHARE {
+                warnings.push(SketchWarning::SkewedRow { row, nonzero: fill, mean });
+            }
+        }
+        if self.total > 0 {
+            let bound = (self.actual_epsilon() * self.total as f64) as u64;
+            let (_, _, largest) = self.dominant_cell();
+            if bound >= largest {
+                warnings.push(SketchWarning::ErrorBoundExceeded { bound, largest });
+            }
+        }
+
+        warnings.sort_by_key(|w| std::cmp::Reverse(w.severity()));
+        warnings
+    }
+}
@@ -0,0 +1,98 @@
+use std::hash::Hash;
+use std::num::NonZeroUsize;
+
+use crate::{CountMinSketch, SketchError};
+
+/// Identifies the shard (node, process, ...) a [`ProvenanceSketch`] was built on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SourceId(pub u16);
+
+/// A [`CountMinSketch`] that tags every cell with the source that dominates it.
+///
+/// Each shard builds its sketch under its own [`SourceId`], and its increments tag the cells
+/// they touch. When sketches are merged, a cell keeps the tag of whichever side contributed
+/// the larger count. `estimate_with_source` then reports the tag of the key's minimizing
+/// cell, i.e. the shard most responsible for the key's estimate, which helps trace a hot key
+/// back to a node after a distributed merge.
+///
+/// The tags cost two extra bytes per cell.
+pub struct ProvenanceSketch {
+    sketch: CountMinSketch,
+    sources: Box<[SourceId]>,
+    source: SourceId,
+}
+
+impl ProvenanceSketch {
+    /// Creates a sketch with explicit `width` and `depth` whose increments are tagged with `source`.
+    ///
+    /// Sketches must share dimensions and seeds to be merged, so every shard should be built
+    /// with the same arguments apart from `source`.
+    pub fn new(width: NonZeroUsize, depth: NonZeroUsize, source: SourceId) -> Self {
+        Self::from_sketch(CountMinSketch::new(width, depth), source)
+    }
+
+    /// Wraps an existing sketch; its current counts are attributed to `source`.
+    pub fn from_sketch(sketch: CountMinSketch, source: SourceId) -> Self {
+        let sources = vec![source; sketch.table.len()].into_boxed_slice();
+        Self { sketch, sources, source }
+    }
+
+    /// Increments the frequency count for the given key, tagging its cells with this sketch's source.
+    pub fn increment<T: Hash + ?Sized>(&mut self, key: &T) {
+        let h1 = self.sketch.hasher.hash_one(key);
+        let (o, d, w, m) = (self.sketch.row_offset, self.sketch.depth, self.sketch.width, self.sketch.width_mask);
+        <CountMinSketch>::calculate_indices(h1, o, d, w, m, |idx| self.sources[idx] = self.source);
+        self.sketch.add_hashed(h1, 1);
+    }
+
+    /// Estimates the frequency count of the given key.
+    #[inline]
+    pub fn estimate<T: Hash + ?Sized>(&self, key: &T) -> u64 {
+        self.sketch.estimate(key)
+    }
+
+    /// Returns the frequency estimate of `key` with the source tag of its minimizing cell.
+    pub fn estimate_with_source<T: Hash + ?Sized>(&self, key: &T) -> (u64, SourceId) {
+        let h1 = self.sketch.hasher.hash_one(key);
+        let (o, d, w, m) = (self.sketch.row_offset, self.sketch.depth, self.sketch.width, self.sketch.width_mask);
+        let mut best: Option<usize> = None;
+        <CountMinSketch>::calculate_indices(h1, o, d, w, m, |idx| {
+            if best.is_none_or(|b| self.sketch.table[idx] < self.sketch.table[b]) {
+                best = Some(idx);
+            }
+        });
+        // `calculate_indices` always yields at least one cell
+        let best = best.unwrap();
+        (self.sketch.table[best], self.sources[best])
+    }
+
+    /// Merges another tagged sketch into this one.
+    ///
+    /// Counts are added as in [`CountMinSketch::merge`], and each cell adopts the other
+    /// sketch's tag when the other side holds the larger count; ties keep the current tag.
+    ///
+    /// # Errors
+    /// Returns an error if the sketches cannot be merged (see [`CountMinSketch::merge`]); neither
+    /// the counts nor the tags are modified in that case.
+    pub fn merge(&mut self, other: &Self) -> Result<(), SketchError> {
+        // Check everything `merge` checks before retagging, so a rejected merge changes nothing
+        self.sketch.check_compatible(&other.sketch)?;
+        let cells = self.sketch.table.iter().zip(other.sketch.table.iter());
+        for ((tag, other_tag), (a, b)) in self.sources.iter_mut().zip(other.sources.iter()).zip(cells) {
+            if b > a {
+                *tag = *other_tag;
+            }
+        }
+        self.sketch.merge(&other.sketch)
+    }
+
+    /// Returns the source tag given to this sketch's own increments.
+    pub fn source(&self) -> SourceId {
+        self.source
+    }
+
+    /// Returns the underlying sketch.
+    pub fn as_sketch(&self) -> &CountMinSketch {
+        &self.sketch
+    }
+}
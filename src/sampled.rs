@@ -0,0 +1,70 @@
+use std::hash::Hash;
+use std::num::NonZeroUsize;
+
+use crate::{splitmix64, CountMinSketch};
+
+/// A [`CountMinSketch`] that also keeps a bounded uniform sample of the raw keys it has seen.
+///
+/// A sketch can estimate any key's frequency but cannot enumerate its keys. During
+/// `increment`, this wrapper maintains a reservoir (Algorithm R) of at most `capacity` keys
+/// drawn uniformly from the stream, so frequent keys are proportionally likely to be in it.
+/// Estimating each sampled key then gives an approximate "top observed keys" report.
+///
+/// Keys are cloned into the reservoir, hence the `K: Hash + Clone` bound; a key is only
+/// cloned when it is actually sampled.
+pub struct SampledSketch<K: Hash + Clone> {
+    sketch: CountMinSketch,
+    reservoir: Vec<K>,
+    capacity: usize,
+    seen: u64,
+    rng: u64,
+}
+
+impl<K: Hash + Clone> SampledSketch<K> {
+    /// Creates a sketch with explicit `width` and `depth` keeping up to `capacity` sampled keys.
+    pub fn new(width: NonZeroUsize, depth: NonZeroUsize, capacity: NonZeroUsize) -> Self {
+        Self::from_sketch(CountMinSketch::new(width, depth), capacity)
+    }
+
+    /// Wraps an existing sketch; only keys incremented from now on are sampled.
+    pub fn from_sketch(sketch: CountMinSketch, capacity: NonZeroUsize) -> Self {
+        Self {
+            rng: sketch.seeds[0],
+            sketch,
+            reservoir: Vec::with_capacity(capacity.get()),
+            capacity: capacity.get(),
+            seen: 0,
+        }
+    }
+
+    /// Increments the frequency count for the given key, possibly sampling it.
+    pub fn increment(&mut self, key: &K) {
+        self.sketch.increment(key);
+        self.seen += 1;
+        if self.reservoir.len() < self.capacity {
+            self.reservoir.push(key.clone());
+            return;
+        }
+        // Uniform slot in [0, seen) by multiply-shift; the key survives with probability capacity / seen
+        let slot = ((splitmix64(&mut self.rng) as u128 * self.seen as u128) >> 64) as usize;
+        if slot < self.capacity {
+            self.reservoir[slot] = key.clone();
+        }
+    }
+
+    /// Estimates the frequency count of the given key.
+    #[inline]
+    pub fn estimate(&self, key: &K) -> u64 {
+        self.sketch.estimate(key)
+    }
+
+    /// Returns the current sample of observed keys. Frequent keys may appear more than once.
+    pub fn sampled_keys(&self) -> &[K] {
+        &self.reservoir
+    }
+
+    /// Returns the underlying sketch.
+    pub fn as_sketch(&self) -> &CountMinSketch {
+        &self.sketch
+    }
+}
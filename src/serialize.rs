@@ -0,0 +1,62 @@
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{CountMinSketch, SketchError, SketchHeader};
+
+/// Wire form of a sketch: the [`SketchHeader`] fields without the magic, then the flat table.
+#[derive(Serialize, Deserialize)]
+struct SketchRepr<T> {
+    version: u32,
+    width: u64,
+    depth: u64,
+    row_offset: u64,
+    seeds: [u64; 4],
+    hash_algo: u64,
+    table: T,
+}
+
+/// Writes the format version, dimensions, seeds, hash algorithm and the row-major table.
+/// Enabled by the `serde` feature.
+impl Serialize for CountMinSketch {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SketchRepr {
+            version: SketchHeader::VERSION,
+            width: self.width as u64,
+            depth: self.depth as u64,
+            row_offset: self.row_offset as u64,
+            seeds: self.seeds,
+            hash_algo: self.hash_algo().id(),
+            table: &self.table[..],
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Rebuilds the hasher from the stored seeds and algorithm, rejecting other format versions,
+/// invalid dimensions and tables whose length is not `width * depth`. Enabled by the `serde` feature.
+impl<'de> Deserialize<'de> for CountMinSketch {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = SketchRepr::<Vec<u64>>::deserialize(deserializer)?;
+        let header = SketchHeader {
+            magic: SketchHeader::MAGIC,
+            version: repr.version,
+            width: repr.width,
+            depth: repr.depth,
+            seeds: repr.seeds,
+            hash_algo: repr.hash_algo,
+            row_offset: repr.row_offset,
+        };
+        let cell_count = header.cell_count().map_err(D::Error::custom)?;
+        if repr.table.len() != cell_count {
+            return Err(D::Error::custom(SketchError::TableLengthMismatch));
+        }
+        Ok(Self::from_parts(
+            repr.width as usize,
+            repr.depth as usize,
+            repr.row_offset as usize,
+            repr.seeds,
+            header.algo(),
+            repr.table.into_boxed_slice(),
+        ))
+    }
+}
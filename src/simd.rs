@@ -0,0 +1,39 @@
+use crate::Counter;
+
+/// Cells processed per step. Eight `u64` lanes fill one AVX-512 register or two AVX2 ones.
+const LANES: usize = 8;
+
+/// Saturating-adds `b` into `a` cell by cell, in fixed-width lane chunks.
+///
+/// `std::simd` needs a nightly compiler, so the chunks are plain arrays: a fixed trip count
+/// with no cross-lane dependency is what LLVM lowers to packed saturating adds on stable.
+pub(crate) fn add_cells<C: Counter>(a: &mut [C], b: &[C]) {
+    let mut a_chunks = a.chunks_exact_mut(LANES);
+    let mut b_chunks = b.chunks_exact(LANES);
+    for (a, b) in (&mut a_chunks).zip(&mut b_chunks) {
+        let a: &mut [C; LANES] = a.try_into().unwrap();
+        let b: &[C; LANES] = b.try_into().unwrap();
+        for lane in 0..LANES {
+            a[lane] = a[lane].saturating_add(b[lane]);
+        }
+    }
+    for (a, &b) in a_chunks.into_remainder().iter_mut().zip(b_chunks.remainder()) {
+        *a = a.saturating_add(b);
+    }
+}
+
+/// Sums the absolute cell differences of two slices, one accumulator per lane.
+///
+/// Each lane sum is bounded by the total, so it overflows exactly when the scalar sum would.
+pub(crate) fn cells_l1(a: &[u64], b: &[u64]) -> u64 {
+    let mut acc = [0u64; LANES];
+    let a_chunks = a.chunks_exact(LANES);
+    let b_chunks = b.chunks_exact(LANES);
+    let rest = a_chunks.remainder().iter().zip(b_chunks.remainder()).map(|(&x, &y)| x.abs_diff(y)).sum::<u64>();
+    for (a, b) in a_chunks.zip(b_chunks) {
+        for lane in 0..LANES {
+            acc[lane] += a[lane].abs_diff(b[lane]);
+        }
+    }
+    acc.iter().sum::<u64>() + rest
+}
@@ -0,0 +1,65 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+
+use crate::CountMinSketch;
+
+/// A pluggable persistence backend for sketches.
+///
+/// Implementations decide *where* a sketch lives (a file, an object store, a key-value
+/// service); the sketch itself only provides the [`to_bytes`](CountMinSketch::to_bytes) /
+/// [`from_bytes`](CountMinSketch::from_bytes) binary format.
+pub trait SketchStore {
+    /// Persists the given sketch, replacing any previously stored one.
+    fn store(&self, sketch: &CountMinSketch) -> io::Result<()>;
+
+    /// Loads the most recently stored sketch.
+    fn load(&self) -> io::Result<CountMinSketch>;
+}
+
+/// A [`SketchStore`] keeping a single sketch in a file, in the binary format.
+///
+/// `store` writes to a temporary file next to the target (the file name with `.tmp`
+/// appended) and renames it over the target, so a crash mid-write leaves the previously
+/// stored sketch intact.
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    path: PathBuf,
+}
+
+impl FileStore {
+    /// Creates a store backed by the file at `path`. Nothing is touched until the first `store`.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Returns the backing file path.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl SketchStore for FileStore {
+    fn store(&self, sketch: &CountMinSketch) -> io::Result<()> {
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(".tmp");
+        let tmp = PathBuf::from(tmp);
+        let written = File::create(&tmp).and_then(|file| {
+            let mut writer = BufWriter::new(file);
+            sketch.write_to(&mut writer)?;
+            writer.into_inner().map_err(io::IntoInnerError::into_error)?.sync_all()
+        });
+        match written.and_then(|()| fs::rename(&tmp, &self.path)) {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                let _ = fs::remove_file(&tmp);
+                Err(e)
+            }
+        }
+    }
+
+    fn load(&self) -> io::Result<CountMinSketch> {
+        let data = fs::read(&self.path)?;
+        CountMinSketch::from_bytes(&data).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
@@ -0,0 +1,124 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::num::NonZeroUsize;
+
+use crate::CountMinSketch;
+
+/// A [`CountMinSketch`] that also tracks the `k` keys with the highest estimates seen so far.
+///
+/// A sketch can only answer for keys it is asked about. On every `increment`, this wrapper
+/// re-estimates the key and keeps it in a bounded min-heap of heavy hitters, evicting the
+/// smallest tracked estimate once `k` keys are held. Keys are stored as owned bytes, so they
+/// must be viewable as `[u8]` (`&str`, `String`, `Vec<u8>`, ...). They are hashed as byte
+/// slices: query this wrapper, not the inner sketch, to get matching estimates.
+///
+/// Updated estimates are pushed as new heap entries; outdated entries are skipped when they
+/// surface and purged once the heap grows to twice `k`.
+pub struct TopKSketch {
+    sketch: CountMinSketch,
+    k: usize,
+    tracked: HashMap<Vec<u8>, u64>,
+    heap: BinaryHeap<Reverse<(u64, Vec<u8>)>>,
+}
+
+impl TopKSketch {
+    /// Creates a sketch with explicit `width` and `depth` tracking the top `k` keys.
+    pub fn new(width: NonZeroUsize, depth: NonZeroUsize, k: NonZeroUsize) -> Self {
+        Self::from_sketch(CountMinSketch::new(width, depth), k)
+    }
+
+    /// Creates a sketch sized from statistical parameters, see [`CountMinSketch::with_params`].
+    pub fn with_params(epsilon: f64, delta: f64, k: NonZeroUsize) -> Self {
+        Self::from_sketch(CountMinSketch::with_params(epsilon, delta), k)
+    }
+
+    /// Wraps an existing sketch; only keys incremented from now on are tracked.
+    pub fn from_sketch(sketch: CountMinSketch, k: NonZeroUsize) -> Self {
+        Self {
+            sketch,
+            k: k.get(),
+            tracked: HashMap::with_capacity(k.get()),
+            heap: BinaryHeap::with_capacity(2 * k.get()),
+        }
+    }
+
+    /// Increments the frequency count for the given key and updates the top-k set.
+    pub fn increment<T: AsRef<[u8]> + ?Sized>(&mut self, key: &T) {
+        let key = key.as_ref();
+        self.sketch.increment(key);
+        let estimate = self.sketch.estimate(key);
+
+        if let Some(tracked) = self.tracked.get_mut(key) {
+            *tracked = estimate;
+        } else if self.tracked.len() < self.k {
+            self.tracked.insert(key.to_vec(), estimate);
+        } else {
+            match self.min_tracked() {
+                Some(min) if estimate > min => {
+                    let Reverse((_, evicted)) = self.heap.pop().expect("min_tracked peeked an entry");
+                    self.tracked.remove(&evicted);
+                    self.tracked.insert(key.to_vec(), estimate);
+                }
+                _ => return,
+            }
+        }
+
+        self.heap.push(Reverse((estimate, key.to_vec())));
+        if self.heap.len() > 2 * self.k {
+            self.heap = self.tracked.iter().map(|(key, &estimate)| Reverse((estimate, key.clone()))).collect();
+        }
+    }
+
+    /// Estimates the frequency count of the given key.
+    #[inline]
+    pub fn estimate<T: AsRef<[u8]> + ?Sized>(&self, key: &T) -> u64 {
+        self.sketch.estimate(key.as_ref())
+    }
+
+    /// Returns the tracked keys with their estimates, highest estimate first.
+    pub fn top_k(&self) -> Vec<(Vec<u8>, u64)> {
+        let mut top: Vec<(Vec<u8>, u64)> = self.tracked.iter().map(|(key, &estimate)| (key.clone(), estimate)).collect();
+        top.sort_unstable_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        top
+    }
+
+    /// Keeps only the `k` tracked keys with the highest estimates, ties broken by key.
+    ///
+    /// The tracking capacity set at construction is unchanged, so later increments can fill
+    /// the freed slots again.
+    pub fn retain_top_k(&mut self, k: usize) {
+        let top = self.top_k();
+        self.tracked = top.into_iter().take(k).collect();
+        self.heap = self.tracked.iter().map(|(key, &estimate)| Reverse((estimate, key.clone()))).collect();
+    }
+
+    /// Multiplies every counter by `factor` (see [`CountMinSketch::decay`]), re-estimates the
+    /// tracked keys and keeps the `k` highest, so old heavy hitters fade out over time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `factor` is not in `[0, 1]`.
+    pub fn retain_top_k_with_decay(&mut self, k: usize, factor: f64) {
+        self.sketch.decay(factor);
+        for (key, estimate) in self.tracked.iter_mut() {
+            *estimate = self.sketch.estimate(key.as_slice());
+        }
+        self.retain_top_k(k);
+    }
+
+    /// Returns the underlying sketch.
+    pub fn as_sketch(&self) -> &CountMinSketch {
+        &self.sketch
+    }
+
+    /// Drops outdated heap entries and returns the smallest tracked estimate.
+    fn min_tracked(&mut self) -> Option<u64> {
+        while let Some(Reverse((estimate, key))) = self.heap.peek() {
+            if self.tracked.get(key) == Some(estimate) {
+                return Some(*estimate);
+            }
+            self.heap.pop();
+        }
+        None
+    }
+}
@@ -0,0 +1,69 @@
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::num::NonZeroUsize;
+
+use crate::CountMinSketch;
+
+/// A [`CountMinSketch`] bound to a single key type.
+///
+/// `CountMinSketch::increment`/`estimate` accept any `Hash` type, so querying a sketch of
+/// `String`s with a `u64` compiles and silently returns a wrong answer (the two types hash
+/// differently). `TypedSketch<K>` only accepts `&K`, turning that mistake into a compile error.
+///
+/// ```
+/// use std::num::NonZeroUsize;
+/// use count_min_sketch_rs::TypedSketch;
+///
+/// let mut sketch = TypedSketch::<String>::new(NonZeroUsize::new(1024).unwrap(), NonZeroUsize::new(4).unwrap());
+/// sketch.increment(&"alice".to_string());
+/// assert_eq!(sketch.estimate(&"alice".to_string()), 1);
+/// ```
+///
+/// Querying with another key type is rejected:
+///
+/// ```compile_fail
+/// use std::num::NonZeroUsize;
+/// use count_min_sketch_rs::TypedSketch;
+///
+/// let sketch = TypedSketch::<String>::new(NonZeroUsize::new(1024).unwrap(), NonZeroUsize::new(4).unwrap());
+/// sketch.estimate(&42u64);
+/// ```
+pub struct TypedSketch<K: Hash + ?Sized> {
+    sketch: CountMinSketch,
+    // `fn(&K)` keeps the wrapper `Send + Sync` regardless of `K`: no `K` is ever stored.
+    _key: PhantomData<fn(&K)>,
+}
+
+impl<K: Hash + ?Sized> TypedSketch<K> {
+    /// Creates a typed sketch with explicit `width` and `depth`, see [`CountMinSketch::new`].
+    pub fn new(width: NonZeroUsize, depth: NonZeroUsize) -> Self {
+        Self::from_sketch(CountMinSketch::new(width, depth))
+    }
+
+    /// Wraps an existing sketch, which from now on only counts keys of type `K`.
+    pub fn from_sketch(sketch: CountMinSketch) -> Self {
+        Self { sketch, _key: PhantomData }
+    }
+
+    /// Increments the frequency count for the given key.
+    #[inline]
+    pub fn increment(&mut self, key: &K) {
+        self.sketch.increment(key);
+    }
+
+    /// Estimates the frequency count of the given key.
+    #[inline]
+    pub fn estimate(&self, key: &K) -> u64 {
+        self.sketch.estimate(key)
+    }
+
+    /// Returns the underlying untyped sketch.
+    pub fn as_sketch(&self) -> &CountMinSketch {
+        &self.sketch
+    }
+
+    /// Unwraps the underlying untyped sketch.
+    pub fn into_inner(self) -> CountMinSketch {
+        self.sketch
+    }
+}
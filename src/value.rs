@@ -0,0 +1,99 @@
+use std::hash::Hash;
+use std::num::NonZeroUsize;
+
+use crate::CountMinSketch;
+
+/// A [`CountMinSketch`] that also tracks the distribution of values recorded per key.
+///
+/// Every sketch cell owns a fixed histogram of `bins` equal-width buckets over
+/// `[min, max]` (values outside are clamped into the edge buckets). `insert(key, value)`
+/// counts the key and adds the value to the histogram of each of the key's cells. `query`
+/// reads the histogram of the key's least-collided cell, the one with the smallest count,
+/// and interpolates quantiles inside it.
+///
+/// Memory grows as `width * depth * bins` counters, so keep the sketch narrow and the
+/// histogram coarse.
+pub struct ValueSketch {
+    sketch: CountMinSketch,
+    histograms: Box<[u64]>,
+    bins: usize,
+    min: f64,
+    max: f64,
+}
+
+impl ValueSketch {
+    /// Creates a value sketch with explicit `width` and `depth` and a `bins`-bucket histogram
+    /// over `[min, max]` per cell.
+    ///
+    /// Panics if `min >= max` or either bound is not finite.
+    pub fn new(width: NonZeroUsize, depth: NonZeroUsize, min: f64, max: f64, bins: NonZeroUsize) -> Self {
+        assert!(min.is_finite() && max.is_finite() && min < max, "min and max must be finite with min < max.");
+        let sketch = CountMinSketch::new(width, depth);
+        let histograms = vec![0u64; sketch.table.len() * bins.get()].into_boxed_slice();
+        Self { sketch, histograms, bins: bins.get(), min, max }
+    }
+
+    /// Counts one occurrence of `key` and records its `value`.
+    pub fn insert<T: Hash + ?Sized>(&mut self, key: &T, value: f64) {
+        let bin = self.bin(value);
+        let h1 = self.sketch.hasher.hash_one(key);
+        let (o, d, w, m) = (self.sketch.row_offset, self.sketch.depth, self.sketch.width, self.sketch.width_mask);
+        <CountMinSketch>::calculate_indices(h1, o, d, w, m, |idx| {
+            let slot = &mut self.histograms[idx * self.bins + bin];
+            *slot = slot.saturating_add(1);
+        });
+        self.sketch.add_hashed(h1, 1);
+    }
+
+    /// Returns the frequency estimate of `key` with the approximate median and 99th
+    /// percentile of its values. The quantiles are `NaN` if the key was never inserted.
+    pub fn query<T: Hash + ?Sized>(&self, key: &T) -> (u64, f64, f64) {
+        let p50 = self.quantile(key, 0.5).unwrap_or(f64::NAN);
+        let p99 = self.quantile(key, 0.99).unwrap_or(f64::NAN);
+        (self.sketch.estimate(key), p50, p99)
+    }
+
+    /// Returns the approximate `q`-quantile (`0.0..=1.0`) of the values recorded for `key`,
+    /// or `None` if the key was never inserted.
+    pub fn quantile<T: Hash + ?Sized>(&self, key: &T, q: f64) -> Option<f64> {
+        let histogram = self.least_collided_histogram(key)?;
+        let n: u64 = histogram.iter().sum();
+        let target = q.clamp(0.0, 1.0) * n as f64;
+        let bin_width = (self.max - self.min) / self.bins as f64;
+
+        let mut cumulative = 0.0;
+        for (bin, &count) in histogram.iter().enumerate() {
+            let count = count as f64;
+            if count > 0.0 && cumulative + count >= target {
+                let fraction = (target - cumulative) / count;
+                return Some(self.min + (bin as f64 + fraction) * bin_width);
+            }
+            cumulative += count;
+        }
+        Some(self.max)
+    }
+
+    /// Returns the underlying frequency sketch.
+    pub fn as_sketch(&self) -> &CountMinSketch {
+        &self.sketch
+    }
+
+    fn bin(&self, value: f64) -> usize {
+        let scaled = (value - self.min) / (self.max - self.min) * self.bins as f64;
+        // NaN and values below `min` saturate to bin 0
+        (scaled as usize).min(self.bins - 1)
+    }
+
+    fn least_collided_histogram<T: Hash + ?Sized>(&self, key: &T) -> Option<&[u64]> {
+        let h1 = self.sketch.hasher.hash_one(key);
+        let (o, d, w, m) = (self.sketch.row_offset, self.sketch.depth, self.sketch.width, self.sketch.width_mask);
+        let mut best: Option<usize> = None;
+        <CountMinSketch>::calculate_indices(h1, o, d, w, m, |idx| {
+            if best.is_none_or(|b| self.sketch.table[idx] < self.sketch.table[b]) {
+                best = Some(idx);
+            }
+        });
+        let best = best.filter(|&idx| self.sketch.table[idx] > 0)?;
+        Some(&self.histograms[best * self.bins..(best + 1) * self.bins])
+    }
+}
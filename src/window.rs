@@ -0,0 +1,55 @@
+use std::hash::Hash;
+use std::num::NonZeroUsize;
+
+use crate::CountMinSketch;
+
+/// A sliding window of counts, kept as a ring of sub-sketches, one per time bucket.
+///
+/// Increments go to the current bucket and estimates add up the estimates of every bucket,
+/// so they cover the last `buckets` periods. The caller marks period boundaries with
+/// [`advance`](Self::advance), e.g. once a minute for a five-bucket "last 5 minutes" window,
+/// which clears the oldest bucket and makes it current. Memory is `buckets` times that of a
+/// single sketch.
+pub struct WindowedCountMinSketch {
+    buckets: Box<[CountMinSketch]>,
+    current: usize,
+}
+
+impl WindowedCountMinSketch {
+    /// Creates a window of `buckets` sub-sketches, each with explicit `width` and `depth`.
+    pub fn new(width: NonZeroUsize, depth: NonZeroUsize, buckets: NonZeroUsize) -> Self {
+        Self {
+            buckets: (0..buckets.get()).map(|_| CountMinSketch::new(width, depth)).collect(),
+            current: 0,
+        }
+    }
+
+    /// Increments the frequency count for the given item in the current bucket.
+    pub fn increment<T: Hash + ?Sized>(&mut self, item: &T) {
+        self.buckets[self.current].increment(item);
+    }
+
+    /// Adds `count` to the frequency of the given item in the current bucket.
+    pub fn increment_by<T: Hash + ?Sized>(&mut self, item: &T, count: u64) {
+        self.buckets[self.current].increment_by(item, count);
+    }
+
+    /// Estimates the frequency of the given item over the whole window.
+    ///
+    /// The sum of the per-bucket estimates, saturating at `u64::MAX`. Each bucket
+    /// overestimates on its own, so the error bounds add up too.
+    pub fn estimate<T: Hash + ?Sized>(&self, item: &T) -> u64 {
+        self.buckets.iter().fold(0u64, |acc, bucket| acc.saturating_add(bucket.estimate(item)))
+    }
+
+    /// Starts a new period: the oldest bucket is cleared and becomes the current one.
+    pub fn advance(&mut self) {
+        self.current = (self.current + 1) % self.buckets.len();
+        self.buckets[self.current].clear();
+    }
+
+    /// Returns the number of buckets the window spans.
+    pub fn buckets(&self) -> usize {
+        self.buckets.len()
+    }
+}
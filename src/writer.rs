@@ -0,0 +1,75 @@
+use std::io;
+
+use crate::CountMinSketch;
+
+/// How a [`SketchWriter`] splits written bytes into keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Tokenize {
+    /// Every buffer passed to `write` is one key. Keys then depend on how the producer
+    /// chunks its output, e.g. on the buffer size of `io::copy`.
+    Chunk,
+    /// Every newline-terminated line is one key, without its `\n`. Lines may span several
+    /// writes; empty lines are skipped.
+    #[default]
+    Lines,
+}
+
+/// An [`io::Write`] adapter counting the written bytes as keys in a sketch.
+///
+/// Lets a byte stream be `io::copy`ed straight into a sketch. Keys are hashed as byte
+/// slices, so query them as `&[u8]` (e.g. `cms.estimate(b"key".as_slice())`). In
+/// [`Tokenize::Lines`] mode the final line may lack a trailing newline; call
+/// [`finish`](Self::finish) at the end of the stream to count it.
+pub struct SketchWriter<'a> {
+    sketch: &'a mut CountMinSketch,
+    mode: Tokenize,
+    pending: Vec<u8>,
+}
+
+impl<'a> SketchWriter<'a> {
+    /// Creates a writer counting into `sketch`, splitting keys according to `mode`.
+    pub fn new(sketch: &'a mut CountMinSketch, mode: Tokenize) -> Self {
+        Self { sketch, mode, pending: Vec::new() }
+    }
+
+    /// Counts the unterminated last line, if any, and releases the sketch.
+    pub fn finish(self) {
+        if !self.pending.is_empty() {
+            self.sketch.increment(&self.pending[..]);
+        }
+    }
+}
+
+impl io::Write for SketchWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self.mode {
+            Tokenize::Chunk => {
+                if !buf.is_empty() {
+                    self.sketch.increment(buf);
+                }
+            }
+            Tokenize::Lines => {
+                let mut lines = buf.split(|&b| b == b'\n');
+                // The last piece has no newline yet: keep it until the line is complete
+                let tail = lines.next_back().unwrap_or_default();
+                for line in lines {
+                    if self.pending.is_empty() {
+                        if !line.is_empty() {
+                            self.sketch.increment(line);
+                        }
+                    } else {
+                        self.pending.extend_from_slice(line);
+                        self.sketch.increment(&self.pending[..]);
+                        self.pending.clear();
+                    }
+                }
+                self.pending.extend_from_slice(tail);
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
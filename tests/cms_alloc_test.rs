@@ -0,0 +1,47 @@
+//! Allocation checks, in their own test binary so the instrumented global allocator only
+//! sees this file's single test.
+
+#[cfg(test)]
+mod tests {
+    use count_min_sketch_rs::{CountMinSketch, HashAlgo};
+    use stats_alloc::{Region, StatsAlloc, INSTRUMENTED_SYSTEM};
+    use std::alloc::System;
+    use std::num::NonZeroUsize;
+
+    #[global_allocator]
+    static GLOBAL: &StatsAlloc<System> = &INSTRUMENTED_SYSTEM;
+
+    #[test]
+    fn test_resets_reuse_table() {
+        let w = NonZeroUsize::new(1024).unwrap();
+        let d = NonZeroUsize::new(4).unwrap();
+        let mut pooled = CountMinSketch::new(w, d);
+        pooled.increment("stale");
+
+        let mut saved = CountMinSketch::with_hash_algo(w, d, [9, 8, 7, 6], HashAlgo::SipHash);
+        for i in 0..100u32 {
+            saved.increment(&i);
+        }
+
+        let region = Region::new(GLOBAL);
+        pooled.reset_to(&saved).unwrap();
+        let stats = region.change();
+        assert_eq!(stats.allocations, 0);
+        assert_eq!(stats.reallocations, 0);
+
+        assert_eq!(pooled.to_bytes(), saved.to_bytes());
+        assert_eq!(pooled.hash_algo(), HashAlgo::SipHash);
+        assert_eq!(pooled.estimate(&42u32), saved.estimate(&42u32));
+
+        let mut narrow = CountMinSketch::new(NonZeroUsize::new(64).unwrap(), d);
+        assert!(narrow.reset_to(&saved).is_err());
+
+        let region = Region::new(GLOBAL);
+        pooled.clear();
+        let stats = region.change();
+        assert_eq!(stats.allocations, 0);
+        assert_eq!(stats.reallocations, 0);
+        assert_eq!(pooled.estimate(&42u32), 0);
+        assert_eq!(pooled.total(), 0);
+    }
+}
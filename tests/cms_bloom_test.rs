@@ -0,0 +1,34 @@
+#[cfg(test)]
+mod tests {
+    use count_min_sketch_rs::CountMinSketch;
+    use std::num::NonZeroUsize;
+
+    #[test]
+    fn test_bloom_matches_sketch_membership() {
+        let mut cms = CountMinSketch::new(NonZeroUsize::new(1024).unwrap(), NonZeroUsize::new(4).unwrap());
+        for i in 0..500u32 {
+            cms.increment(&i);
+        }
+
+        let bloom = cms.to_bloom();
+        for i in 0..500u32 {
+            assert!(bloom.contains(&i));
+        }
+        // Absent keys, including the sketch's false positives, get the same answer
+        for i in 500..5_000u32 {
+            assert_eq!(bloom.contains(&i), cms.contains(&i));
+        }
+        assert_eq!(bloom.bitmap_bytes() * 64, 1024 * 4 * 8);
+    }
+
+    #[test]
+    fn test_bloom_of_split_half() {
+        let mut cms = CountMinSketch::new(NonZeroUsize::new(256).unwrap(), NonZeroUsize::new(4).unwrap());
+        cms.increment("present");
+        let (_, lower) = cms.split_rows(1).unwrap();
+
+        let bloom = lower.to_bloom();
+        assert!(bloom.contains("present"));
+        assert!(!CountMinSketch::new(NonZeroUsize::new(8).unwrap(), NonZeroUsize::new(1).unwrap()).to_bloom().contains("x"));
+    }
+}
@@ -0,0 +1,63 @@
+#[cfg(test)]
+mod tests {
+    use count_min_sketch_rs::{BuildError, CountMinSketch};
+
+    #[test]
+    fn test_build_from_epsilon_delta() {
+        let cms = CountMinSketch::builder().epsilon(0.01).delta(0.001).build().unwrap();
+        let reference = CountMinSketch::with_params(0.01, 0.001);
+        assert_eq!((cms.get_width(), cms.get_depth()), (reference.get_width(), reference.get_depth()));
+        assert_eq!(cms.get_seeds(), reference.get_seeds());
+
+        let explicit = CountMinSketch::builder().width(1000).depth(5).seeds([1, 2, 3, 4]).build().unwrap();
+        assert_eq!((explicit.get_width(), explicit.get_depth()), (1024, 8));
+        assert_eq!(explicit.get_seeds(), [1, 2, 3, 4]);
+
+        let default = CountMinSketch::default();
+        assert_eq!((default.get_width(), default.get_depth()), (4096, 8));
+        assert_eq!(CountMinSketch::builder().build().unwrap().get_width(), default.get_width());
+    }
+
+    #[test]
+    fn test_build_errors() {
+        assert_eq!(CountMinSketch::builder().width(0).build().err(), Some(BuildError::ZeroDimension("width")));
+        assert_eq!(CountMinSketch::builder().depth(0).build().err(), Some(BuildError::ZeroDimension("depth")));
+        assert_eq!(
+            CountMinSketch::builder().width(64).epsilon(0.1).build().err(),
+            Some(BuildError::Conflict { dimension: "width", parameter: "epsilon" })
+        );
+        assert_eq!(
+            CountMinSketch::builder().delta(1.5).build().err(),
+            Some(BuildError::OutOfRange { parameter: "delta", value: 1.5 })
+        );
+        assert_eq!(CountMinSketch::builder().width(usize::MAX).build().err(), Some(BuildError::TooLarge));
+        // Addressable cell count, but more bytes than one allocation can hold
+        assert_eq!(CountMinSketch::builder().width(1 << 62).depth(1).build().err(), Some(BuildError::TooLarge));
+        assert_eq!(CountMinSketch::builder().epsilon(1e-300).build().err(), Some(BuildError::TooLarge));
+    }
+
+    #[test]
+    fn test_sampling_rate_scales_updates() {
+        let mut cms = CountMinSketch::builder().width(1024).depth(4).sampling_rate(0.01).build().unwrap();
+        assert_eq!(cms.sampling_rate(), 0.01);
+
+        cms.increment("sampled");
+        assert_eq!(cms.estimate("sampled"), 100);
+        cms.increment_by("bulk", 3);
+        assert_eq!(cms.estimate("bulk"), 300);
+        assert_eq!(cms.total(), 400);
+
+        assert_eq!(CountMinSketch::default().sampling_rate(), 1.0);
+        assert_eq!(
+            CountMinSketch::builder().sampling_rate(0.0).build().err(),
+            Some(BuildError::OutOfRange { parameter: "sampling_rate", value: 0.0 })
+        );
+        // 1 / 0.3 has no integer weight; rounding it to 3 would inflate every estimate by 10%
+        assert_eq!(
+            CountMinSketch::builder().sampling_rate(0.3).build().err(),
+            Some(BuildError::InexactSamplingRate(0.3))
+        );
+        let third = CountMinSketch::builder().sampling_rate(1.0 / 3.0).build().unwrap();
+        assert_eq!(third.sampling_rate(), 1.0 / 3.0);
+    }
+}
\ No newline at end of file
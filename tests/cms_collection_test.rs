@@ -0,0 +1,61 @@
+#![cfg(feature = "serde")]
+
+#[cfg(test)]
+mod tests {
+    use count_min_sketch_rs::{CountMinSketch, HashAlgo, SketchCollection, SketchError};
+    use std::num::NonZeroUsize;
+
+    fn window(i: u32) -> CountMinSketch {
+        let mut cms = CountMinSketch::with_seeds(NonZeroUsize::new(64).unwrap(), NonZeroUsize::new(2).unwrap(), [1, 2, 3, 4]);
+        for k in 0..=i {
+            cms.increment(&k);
+        }
+        cms
+    }
+
+    #[test]
+    fn test_collection_is_compact_and_round_trips() {
+        let mut collection = SketchCollection::new();
+        let mut individual_bytes = 0;
+        for i in 0..100 {
+            let mut single = SketchCollection::new();
+            single.push(window(i)).unwrap();
+            individual_bytes += serde_json::to_vec(&single).unwrap().len();
+            collection.push(window(i)).unwrap();
+        }
+
+        let json = serde_json::to_vec(&collection).unwrap();
+        assert!(json.len() < individual_bytes);
+
+        let restored: SketchCollection = serde_json::from_slice(&json).unwrap();
+        assert_eq!(restored.len(), 100);
+        for (i, sketch) in restored.iter().enumerate() {
+            assert_eq!(sketch.to_bytes(), window(i as u32).to_bytes());
+            assert!(sketch.estimate(&(i as u32)) >= 1);
+        }
+    }
+
+    #[test]
+    fn test_collection_rejects_mismatched_members() {
+        let mut collection = SketchCollection::new();
+        collection.push(window(0)).unwrap();
+
+        let wider = CountMinSketch::with_seeds(NonZeroUsize::new(128).unwrap(), NonZeroUsize::new(2).unwrap(), [1, 2, 3, 4]);
+        assert_eq!(collection.push(wider), Err(SketchError::IncompatibleDimensions { left: (64, 2), right: (128, 2) }));
+        let reseeded = CountMinSketch::with_seeds(NonZeroUsize::new(64).unwrap(), NonZeroUsize::new(2).unwrap(), [5, 6, 7, 8]);
+        assert_eq!(collection.push(reseeded), Err(SketchError::IncompatibleSeeds));
+        let fx = CountMinSketch::with_hash_algo(NonZeroUsize::new(64).unwrap(), NonZeroUsize::new(2).unwrap(), [1, 2, 3, 4], HashAlgo::FxHash);
+        assert_eq!(collection.push(fx), Err(SketchError::IncompatibleHashAlgorithm));
+        assert_eq!(collection.len(), 1);
+    }
+
+    #[test]
+    fn test_empty_and_malformed_collections() {
+        let empty = SketchCollection::new();
+        let restored: SketchCollection = serde_json::from_slice(&serde_json::to_vec(&empty).unwrap()).unwrap();
+        assert!(restored.is_empty());
+
+        let bad = r#"{"width":64,"depth":2,"row_offset":0,"seeds":[1,2,3,4],"hash_algo":0,"tables":[[1,2,3]]}"#;
+        assert!(serde_json::from_str::<SketchCollection>(bad).is_err());
+    }
+}
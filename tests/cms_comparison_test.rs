@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod tests {
-    use count_min_sketch_rs::CountMinSketch;
+    use count_min_sketch_rs::{CountMinSketch, SketchHeader};
+    use rand::prelude::*;
     use std::num::NonZeroUsize;
 
     fn setup_sketch() -> CountMinSketch {
@@ -104,6 +105,163 @@ mod tests {
         let cms2 = CountMinSketch::new(NonZeroUsize::new(512).unwrap(), NonZeroUsize::new(4).unwrap());
 
         assert!(cms1.l1_distance(&cms2).is_err());
+        assert!(cms1.linf_distance(&cms2).is_err());
         assert!(cms1.cosine_similarity(&cms2).is_err());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_linf_distance() {
+        let mut cms1 = setup_sketch();
+        let mut cms2 = setup_sketch();
+
+        // Shared background traffic cancels out
+        for item in ["apple", "banana", "cherry"] {
+            for _ in 0..20 {
+                cms1.increment(item);
+                cms2.increment(item);
+            }
+        }
+        assert_eq!(cms1.linf_distance(&cms2).unwrap(), 0);
+
+        // One new dominant hitter on one side only
+        for _ in 0..50 { cms1.increment("hitter"); }
+        for _ in 0..3 { cms2.increment("minor"); }
+
+        let dist = cms1.linf_distance(&cms2).unwrap();
+        assert_eq!(dist, 50, "L-infinity should reflect the single largest divergence");
+        assert!(cms1.l1_distance(&cms2).unwrap() >= 53);
+    }
+
+    #[test]
+    fn test_normalize_to_equal_footing() {
+        let w = NonZeroUsize::try_from(1024usize).unwrap();
+        let d = NonZeroUsize::try_from(4usize).unwrap();
+        let mut small = CountMinSketch::new(w, d);
+        let mut large = CountMinSketch::new(w, d);
+
+        // Same distribution, sampled ten times more heavily in `large`
+        for k in 0..100u32 {
+            for _ in 0..(k + 1) {
+                small.increment(&k);
+            }
+            for _ in 0..10 * (k + 1) {
+                large.increment(&k);
+            }
+        }
+
+        let cosine_before = small.cosine_similarity(&large).unwrap();
+        let l1_before = small.l1_distance(&large).unwrap();
+
+        small.normalize_to(10_000);
+        large.normalize_to(10_000);
+
+        // Both now hold ~198 counts for key 99; rounding moves each cell by at most half a count
+        let (estimate_small, estimate_large) = (small.estimate(&99u32), large.estimate(&99u32));
+        assert!(estimate_small.abs_diff(estimate_large) <= 2);
+
+        // Cosine is scale-invariant, so normalization must preserve it, while the
+        // scale-sensitive L1 distance collapses towards zero
+        let cosine_after = small.cosine_similarity(&large).unwrap();
+        assert!((cosine_after - cosine_before).abs() < 1e-3);
+        assert!(cosine_after > 0.999);
+        let l1_after = small.l1_distance(&large).unwrap();
+        assert!(l1_after * 100 < l1_before);
+    }
+
+    #[test]
+    fn test_normalize_to_empty_and_zero_target() {
+        let w = NonZeroUsize::try_from(64usize).unwrap();
+        let d = NonZeroUsize::try_from(2usize).unwrap();
+        let mut empty = CountMinSketch::new(w, d);
+        empty.normalize_to(1_000);
+        assert_eq!(empty.estimate("a"), 0);
+
+        let mut cms = CountMinSketch::new(w, d);
+        cms.increment("a");
+        cms.normalize_to(0);
+        assert_eq!(cms.estimate("a"), 0);
+    }
+
+    #[test]
+    fn test_entropy_uniform_vs_dominated() {
+        let w = NonZeroUsize::try_from(4096usize).unwrap();
+        let d = NonZeroUsize::try_from(4usize).unwrap();
+        let mut uniform = CountMinSketch::new(w, d);
+        let mut dominated = CountMinSketch::new(w, d);
+
+        for i in 0..10_000u32 {
+            uniform.increment(&(i % 256));
+            dominated.increment(&(if i % 100 == 0 { i } else { 0 }));
+        }
+
+        // 256 equally likely keys: log2(256) = 8 bits, minus a little for collisions
+        let h_uniform = uniform.entropy();
+        assert!(h_uniform > 7.5 && h_uniform <= 8.0 + 1e-9, "{h_uniform}");
+
+        // One key holds 99% of the mass
+        let h_dominated = dominated.entropy();
+        assert!(h_dominated < 1.0, "{h_dominated}");
+
+        assert_eq!(CountMinSketch::new(w, d).entropy(), 0.0);
+    }
+
+    fn cells(cms: &CountMinSketch) -> Vec<u64> {
+        cms.to_bytes()[SketchHeader::LEN..]
+            .chunks(8)
+            .map(|c| u64::from_le_bytes(c.try_into().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_distances_match_serial_reference() {
+        // Rows span several chunks, exercising the split accumulation of the `rayon` feature
+        let (w, d) = (1usize << 16, 4usize);
+        let mut a = CountMinSketch::new(NonZeroUsize::new(w).unwrap(), NonZeroUsize::new(d).unwrap());
+        let mut b = CountMinSketch::new(NonZeroUsize::new(w).unwrap(), NonZeroUsize::new(d).unwrap());
+        for i in 0..200_000u64 {
+            a.increment(&(i % 50_000));
+            b.increment(&(i % 30_011));
+        }
+
+        let (ca, cb) = (cells(&a), cells(&b));
+        let mut l1 = u64::MAX;
+        let mut cosine: f64 = 0.0;
+        for (ra, rb) in ca.chunks(w).zip(cb.chunks(w)) {
+            l1 = l1.min(ra.iter().zip(rb).map(|(&x, &y)| x.abs_diff(y)).sum());
+            let dot: f64 = ra.iter().zip(rb).map(|(&x, &y)| x as f64 * y as f64).sum();
+            let n_a: f64 = ra.iter().map(|&x| (x * x) as f64).sum();
+            let n_b: f64 = rb.iter().map(|&y| (y * y) as f64).sum();
+            cosine = cosine.max(dot / (n_a.sqrt() * n_b.sqrt()));
+        }
+
+        assert_eq!(a.l1_distance(&b).unwrap(), l1);
+        assert!((a.cosine_similarity(&b).unwrap() - cosine).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_merge_and_l1_match_scalar_reference_on_random_tables() {
+        // Widths below, at and above the `simd` feature's lane count cover the remainder
+        // handling; cells near the maximum exercise the saturating add
+        let mut rng = StdRng::seed_from_u64(280);
+        for w in [1usize, 2, 4, 8, 16, 1024] {
+            let random = |rng: &mut StdRng, saturating: bool| {
+                let mut cms = CountMinSketch::new(NonZeroUsize::new(w).unwrap(), NonZeroUsize::new(3).unwrap());
+                for index in 0..w * 3 {
+                    let value = if saturating && rng.random_bool(0.2) { u64::MAX - rng.random_range(0..4) } else { rng.random_range(0..1 << 40) };
+                    cms.set_cell(index, value);
+                }
+                cms
+            };
+
+            let (a, b) = (random(&mut rng, false), random(&mut rng, false));
+            let (ca, cb) = (cells(&a), cells(&b));
+            let l1 = ca.chunks(w).zip(cb.chunks(w)).map(|(ra, rb)| ra.iter().zip(rb).map(|(&x, &y)| x.abs_diff(y)).sum::<u64>());
+            assert_eq!(a.l1_distance(&b).unwrap(), l1.min().unwrap());
+
+            let (mut a, b) = (random(&mut rng, true), random(&mut rng, true));
+            let merged: Vec<u64> = cells(&a).iter().zip(&cells(&b)).map(|(&x, &y)| x.saturating_add(y)).collect();
+            a.merge(&b).unwrap();
+            assert_eq!(cells(&a), merged);
+        }
+    }
+}
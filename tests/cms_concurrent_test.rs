@@ -0,0 +1,70 @@
+#[cfg(test)]
+mod tests {
+    use count_min_sketch_rs::{ConcurrentCountMinSketch, Consistency, CountMinSketch};
+    use std::num::NonZeroUsize;
+
+    #[test]
+    fn test_concurrent_increments() {
+        let cms = ConcurrentCountMinSketch::new(NonZeroUsize::new(1024).unwrap(), NonZeroUsize::new(4).unwrap());
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| {
+                    for _ in 0..10_000 {
+                        cms.increment("shared");
+                    }
+                });
+            }
+        });
+        assert_eq!(cms.estimate("shared"), 80_000);
+    }
+
+    #[test]
+    fn test_snapshot_matches_sequential_sketch() {
+        let (w, d) = (NonZeroUsize::new(256).unwrap(), NonZeroUsize::new(4).unwrap());
+        let concurrent = ConcurrentCountMinSketch::new(w, d);
+        let mut sequential = CountMinSketch::new(w, d);
+        for i in 0..2_000u32 {
+            concurrent.increment(&(i % 300));
+            sequential.increment(&(i % 300));
+        }
+
+        let snapshot = concurrent.snapshot();
+        assert_eq!(snapshot.total(), 2_000);
+        assert_eq!(snapshot.l1_distance(&sequential), Ok(0));
+        for i in 0..300u32 {
+            assert_eq!(concurrent.estimate(&i), sequential.estimate(&i));
+        }
+    }
+
+    #[test]
+    fn test_row_consistent_reads_are_never_torn() {
+        let (w, d) = (NonZeroUsize::new(1024).unwrap(), NonZeroUsize::new(8).unwrap());
+        let cms = ConcurrentCountMinSketch::with_consistency(w, d, [1, 2, 3, 4], Consistency::RowConsistent);
+        assert_eq!(cms.consistency(), Consistency::RowConsistent);
+        let done = std::sync::atomic::AtomicBool::new(false);
+        std::thread::scope(|scope| {
+            let writers: Vec<_> = (0..4)
+                .map(|_| {
+                    scope.spawn(|| {
+                        for _ in 0..20_000 {
+                            cms.increment("row");
+                        }
+                    })
+                })
+                .collect();
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    while !done.load(std::sync::atomic::Ordering::Relaxed) {
+                        let cells = cms.cells("row");
+                        assert!(cells.iter().all(|&c| c == cells[0]), "torn read: {cells:?}");
+                    }
+                });
+            }
+            for writer in writers {
+                writer.join().unwrap();
+            }
+            done.store(true, std::sync::atomic::Ordering::Relaxed);
+        });
+        assert_eq!(cms.cells("row"), vec![80_000; 8]);
+    }
+}
\ No newline at end of file
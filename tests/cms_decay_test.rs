@@ -0,0 +1,120 @@
+#[cfg(test)]
+mod tests {
+    use count_min_sketch_rs::{Clock, CountMinSketch, DecayEvery, TimedDecaySketch};
+    use std::cell::Cell;
+    use std::num::NonZeroUsize;
+    use std::rc::Rc;
+    use std::time::{Duration, Instant};
+
+    /// A clock that only moves when the test advances it.
+    #[derive(Clone)]
+    struct ManualClock(Rc<Cell<Instant>>);
+
+    impl ManualClock {
+        fn advance(&self, by: Duration) {
+            self.0.set(self.0.get() + by);
+        }
+    }
+
+    impl Clock for ManualClock {
+        fn now(&self) -> Instant {
+            self.0.get()
+        }
+    }
+
+    fn setup(half_life: Duration) -> (TimedDecaySketch<ManualClock>, ManualClock) {
+        let clock = ManualClock(Rc::new(Cell::new(Instant::now())));
+        let cms = CountMinSketch::new(NonZeroUsize::new(1024).unwrap(), NonZeroUsize::new(4).unwrap());
+        (TimedDecaySketch::with_clock(cms, half_life, clock.clone()), clock)
+    }
+
+    #[test]
+    fn test_half_life_halves_estimate() {
+        let half_life = Duration::from_secs(60);
+        let (mut sketch, clock) = setup(half_life);
+        sketch.increment_by("key", 1000);
+        assert_eq!(sketch.estimate("key"), 1000);
+
+        clock.advance(half_life);
+        assert_eq!(sketch.estimate("key"), 500);
+        // The estimate is a read-only view until the next update applies the decay
+        assert_eq!(sketch.as_sketch().estimate("key"), 1000);
+
+        sketch.increment("key");
+        assert_eq!(sketch.as_sketch().estimate("key"), 501);
+
+        clock.advance(half_life * 2);
+        assert_eq!(sketch.estimate("key"), 125);
+    }
+
+    #[test]
+    fn test_partial_half_life_carries_over() {
+        let half_life = Duration::from_secs(10);
+        let (mut sketch, clock) = setup(half_life);
+        sketch.increment_by("key", 800);
+
+        clock.advance(Duration::from_secs(15));
+        sketch.increment_by("other", 1);
+        assert_eq!(sketch.estimate("key"), 400);
+
+        // 5 s left over from the previous step, so 5 more seconds complete a half-life
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(sketch.estimate("key"), 200);
+    }
+
+    #[test]
+    fn test_decay_scales_estimate_and_total() {
+        let mut cms = CountMinSketch::new(NonZeroUsize::new(1024).unwrap(), NonZeroUsize::new(4).unwrap());
+        cms.increment_by("key", 100);
+        cms.increment_by("other", 40);
+        cms.decay(0.5);
+        assert_eq!(cms.estimate("key"), 50);
+        assert_eq!(cms.estimate("other"), 20);
+        assert_eq!(cms.total(), 70);
+
+        // Recent increments now dominate the faded history
+        cms.increment_by("other", 40);
+        assert!(cms.estimate("other") > cms.estimate("key"));
+    }
+
+    #[test]
+    fn test_decay_floors_counts() {
+        let mut cms = CountMinSketch::new(NonZeroUsize::new(1024).unwrap(), NonZeroUsize::new(4).unwrap());
+        cms.increment_by("a", 7);
+        cms.increment("b");
+        cms.decay(0.5);
+        assert_eq!(cms.estimate("a"), 3);
+        assert_eq!(cms.estimate("b"), 0);
+        cms.decay(1.0);
+        assert_eq!(cms.estimate("a"), 3);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_decay_rejects_growth() {
+        let mut cms = CountMinSketch::new(NonZeroUsize::new(64).unwrap(), NonZeroUsize::new(2).unwrap());
+        cms.decay(1.5);
+    }
+
+    #[test]
+    fn test_decay_every_n_increments() {
+        let cms = CountMinSketch::new(NonZeroUsize::new(1024).unwrap(), NonZeroUsize::new(4).unwrap());
+        let n = NonZeroUsize::new(10).unwrap();
+        let mut decaying = DecayEvery::new(cms, n, 0.5);
+        assert_eq!((decaying.n(), decaying.factor()), (10, 0.5));
+
+        for _ in 0..9 {
+            decaying.increment("key");
+        }
+        assert_eq!(decaying.estimate("key"), 9);
+        decaying.increment("key");
+        assert_eq!(decaying.estimate("key"), 5);
+
+        for _ in 0..10 {
+            decaying.increment("key");
+        }
+        // (10 * 0.5 + 10) * 0.5
+        assert_eq!(decaying.estimate("key"), 7);
+        assert_eq!(decaying.as_sketch().total(), 7);
+    }
+}
\ No newline at end of file
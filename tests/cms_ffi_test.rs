@@ -0,0 +1,25 @@
+#![cfg(feature = "ffi")]
+
+#[cfg(test)]
+mod tests {
+    use count_min_sketch_rs::ffi::{cms_estimate, cms_free, cms_increment, cms_new};
+
+    #[test]
+    fn test_ffi_lifecycle() {
+        let sketch = cms_new(1000, 4);
+        assert!(!sketch.is_null());
+
+        let key = b"from_c";
+        unsafe {
+            for _ in 0..3 {
+                cms_increment(sketch, key.as_ptr(), key.len());
+            }
+            assert_eq!(cms_estimate(sketch, key.as_ptr(), key.len()), 3);
+            assert_eq!(cms_estimate(sketch, b"other".as_ptr(), 5), 0);
+            assert_eq!(cms_estimate(std::ptr::null(), key.as_ptr(), key.len()), 0);
+            cms_free(sketch);
+        }
+
+        assert!(cms_new(0, 4).is_null());
+    }
+}
@@ -0,0 +1,55 @@
+#[cfg(test)]
+mod tests {
+    use count_min_sketch_rs::{CountMinSketch, Severity, SketchWarning};
+    use std::num::NonZeroUsize;
+
+    #[test]
+    fn test_healthy_sketch_has_no_warnings() {
+        let mut cms = CountMinSketch::new(NonZeroUsize::new(1024).unwrap(), NonZeroUsize::new(4).unwrap());
+        assert!(cms.health_check().is_empty());
+        for i in 0..50u32 {
+            cms.increment(&i);
+        }
+        cms.increment_by("hot", 1_000);
+        assert!(cms.health_check().is_empty());
+    }
+
+    #[test]
+    fn test_unhealthy_sketch_reports_saturation_and_fill() {
+        let mut cms = CountMinSketch::new(NonZeroUsize::new(16).unwrap(), NonZeroUsize::new(1).unwrap());
+        for i in 0..1_000u32 {
+            cms.increment(&i);
+        }
+        cms.increment_by("flood", u64::MAX);
+
+        let warnings = cms.health_check();
+        assert_eq!(warnings[0], SketchWarning::SaturatedCells { count: 1 });
+        assert_eq!(warnings[0].severity(), Severity::Critical);
+        assert!(warnings.contains(&SketchWarning::HighFillRatio { ratio: 1.0 }));
+        assert!(warnings.contains(&SketchWarning::SingleRow));
+        assert_eq!(warnings.last().unwrap().severity(), Severity::Info);
+        assert_eq!(warnings[0].to_string(), "1 cells are saturated at u64::MAX");
+    }
+
+    #[test]
+    fn test_skewed_row() {
+        let mut cms = CountMinSketch::new(NonZeroUsize::new(64).unwrap(), NonZeroUsize::new(2).unwrap());
+        for i in 0..64 {
+            cms.set_cell(i, 10);
+        }
+        cms.set_cell(64, 640);
+        assert!(cms.health_check().contains(&SketchWarning::SkewedRow { row: 1, nonzero: 1, mean: 32.5 }));
+    }
+
+    #[test]
+    fn test_error_bound_exceeded_on_uniform_load() {
+        let mut cms = CountMinSketch::new(NonZeroUsize::new(64).unwrap(), NonZeroUsize::new(2).unwrap());
+        // Every cell holds the same count, so no key stands out from the noise
+        for i in 0..128 {
+            cms.set_cell(i, 10);
+        }
+        let warnings = cms.health_check();
+        assert!(warnings.contains(&SketchWarning::ErrorBoundExceeded { bound: 27, largest: 10 }));
+        assert!(warnings.iter().all(|w| w.severity() == Severity::Warning));
+    }
+}
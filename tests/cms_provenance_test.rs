@@ -0,0 +1,81 @@
+#[cfg(test)]
+mod tests {
+    use count_min_sketch_rs::{CountMinSketch, ProvenanceSketch, SketchError, SourceId};
+    use std::num::NonZeroUsize;
+
+    fn shard(id: u16) -> ProvenanceSketch {
+        ProvenanceSketch::new(NonZeroUsize::new(1024).unwrap(), NonZeroUsize::new(4).unwrap(), SourceId(id))
+    }
+
+    #[test]
+    fn test_source_of_shard_only_key() {
+        let mut shard1 = shard(1);
+        let mut shard2 = shard(2);
+
+        for i in 0..200u32 {
+            shard1.increment(&i);
+        }
+        for _ in 0..50 {
+            shard2.increment("hot.key");
+        }
+
+        shard1.merge(&shard2).unwrap();
+        let (estimate, source) = shard1.estimate_with_source("hot.key");
+        assert!(estimate >= 50);
+        assert_eq!(source, SourceId(2));
+
+        // Keys only seen on shard 1 keep its tag
+        let (_, source) = shard1.estimate_with_source(&7u32);
+        assert_eq!(source, SourceId(1));
+    }
+
+    #[test]
+    fn test_merge_into_aggregator() {
+        let mut aggregator = shard(0);
+        let mut shard1 = shard(1);
+        let mut shard2 = shard(2);
+        for _ in 0..30 {
+            shard1.increment("a");
+        }
+        for _ in 0..10 {
+            shard1.increment("b");
+        }
+        for _ in 0..40 {
+            shard2.increment("b");
+        }
+
+        aggregator.merge(&shard1).unwrap();
+        aggregator.merge(&shard2).unwrap();
+
+        assert_eq!(aggregator.estimate_with_source("a"), (30, SourceId(1)));
+        // shard 2 contributed 40 of the 50 counts of "b"
+        assert_eq!(aggregator.estimate_with_source("b"), (50, SourceId(2)));
+        assert_eq!(aggregator.source(), SourceId(0));
+    }
+
+    #[test]
+    fn test_merge_incompatible_dimensions() {
+        let mut a = shard(1);
+        let b = ProvenanceSketch::new(NonZeroUsize::new(64).unwrap(), NonZeroUsize::new(4).unwrap(), SourceId(2));
+        assert!(a.merge(&b).is_err());
+    }
+
+    #[test]
+    fn test_failed_merge_leaves_provenance_unchanged() {
+        let (w, d) = (NonZeroUsize::new(64).unwrap(), NonZeroUsize::new(4).unwrap());
+        let mut a = ProvenanceSketch::new(w, d, SourceId(1));
+        for _ in 0..5 {
+            a.increment("key");
+        }
+        // Every cell of the other side outweighs "key", so any retagging would show
+        let mut b = ProvenanceSketch::from_sketch(CountMinSketch::with_seeds(w, d, [9, 9, 9, 9]), SourceId(2));
+        for i in 0..5_000u32 {
+            b.increment(&i);
+        }
+        assert!(b.as_sketch().rows().flatten().all(|&cell| cell > 5));
+
+        assert_eq!(a.merge(&b).err(), Some(SketchError::IncompatibleSeeds));
+        assert_eq!(a.estimate_with_source("key"), (5, SourceId(1)));
+        assert_eq!(a.as_sketch().total(), 5);
+    }
+}
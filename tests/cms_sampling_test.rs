@@ -0,0 +1,30 @@
+#![cfg(feature = "sampling")]
+
+#[cfg(test)]
+mod tests {
+    use count_min_sketch_rs::SampledSketch;
+    use std::num::NonZeroUsize;
+
+    #[test]
+    fn test_sampled_keys() {
+        let mut cms = SampledSketch::new(
+            NonZeroUsize::new(4096).unwrap(),
+            NonZeroUsize::new(4).unwrap(),
+            NonZeroUsize::new(32).unwrap()
+        );
+
+        // "hot" makes up half of the stream, the rest is a long tail
+        for i in 0..10_000u64 {
+            let key = if i % 2 == 0 { "hot".to_string() } else { format!("tail_{}", i) };
+            cms.increment(&key);
+        }
+
+        let sample = cms.sampled_keys();
+        assert_eq!(sample.len(), 32);
+        assert!(sample.iter().any(|k| k == "hot"), "The dominant key should be sampled");
+
+        let top = sample.iter().max_by_key(|k| cms.estimate(k)).unwrap();
+        assert_eq!(top, "hot");
+        assert!(cms.estimate(top) >= 5_000);
+    }
+}
@@ -0,0 +1,39 @@
+#![cfg(feature = "serde")]
+
+#[cfg(test)]
+mod tests {
+    use count_min_sketch_rs::{CountMinSketch, HashAlgo};
+    use std::num::NonZeroUsize;
+
+    #[test]
+    fn test_json_round_trip() {
+        let w = NonZeroUsize::new(64).unwrap();
+        let d = NonZeroUsize::new(4).unwrap();
+        let mut cms = CountMinSketch::with_hash_algo(w, d, [1, 2, 3, 4], HashAlgo::SipHash);
+        for i in 0..500u32 {
+            cms.increment(&(i % 37));
+        }
+
+        let json = serde_json::to_string(&cms).unwrap();
+        let restored: CountMinSketch = serde_json::from_str(&json).unwrap();
+
+        assert!(restored == cms);
+        assert_eq!(restored.hash_algo(), HashAlgo::SipHash);
+        assert_eq!(restored.total(), cms.total());
+        for i in 0..50u32 {
+            assert_eq!(restored.estimate(&i), cms.estimate(&i));
+        }
+    }
+
+    #[test]
+    fn test_json_rejects_wrong_table_length() {
+        let cms = CountMinSketch::new(NonZeroUsize::new(8).unwrap(), NonZeroUsize::new(2).unwrap());
+        let mut value = serde_json::to_value(&cms).unwrap();
+        value["table"].as_array_mut().unwrap().pop();
+        assert!(serde_json::from_value::<CountMinSketch>(value.clone()).is_err());
+
+        value["table"].as_array_mut().unwrap().push(0.into());
+        value["version"] = 99.into();
+        assert!(serde_json::from_value::<CountMinSketch>(value).is_err());
+    }
+}
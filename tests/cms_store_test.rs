@@ -0,0 +1,236 @@
+#[cfg(test)]
+mod tests {
+    use count_min_sketch_rs::{CountMinSketch, FileStore, HashAlgo, SketchHeader, SketchStore, SketchError};
+    use std::cell::RefCell;
+    use std::io;
+    use std::num::NonZeroUsize;
+
+    /// Keeps the serialized sketch in memory, standing in for a remote backend.
+    #[derive(Default)]
+    struct MemoryStore {
+        bytes: RefCell<Vec<u8>>,
+    }
+
+    impl SketchStore for MemoryStore {
+        fn store(&self, sketch: &CountMinSketch) -> io::Result<()> {
+            *self.bytes.borrow_mut() = sketch.to_bytes();
+            Ok(())
+        }
+
+        fn load(&self) -> io::Result<CountMinSketch> {
+            CountMinSketch::from_bytes(&self.bytes.borrow())
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        }
+    }
+
+    fn populated_sketch() -> CountMinSketch {
+        let mut cms = CountMinSketch::with_seeds(
+            NonZeroUsize::new(1024).unwrap(),
+            NonZeroUsize::new(4).unwrap(),
+            [1, 2, 3, 4]
+        );
+        for i in 0..200 {
+            cms.increment(&format!("key_{}", i % 17));
+        }
+        cms
+    }
+
+    fn assert_round_trip(store: &impl SketchStore) {
+        let cms = populated_sketch();
+        store.store(&cms).expect("Store should succeed");
+        let loaded = store.load().expect("Load should succeed");
+
+        assert_eq!(loaded.to_bytes(), cms.to_bytes());
+        for i in 0..17 {
+            let key = format!("key_{}", i);
+            assert_eq!(loaded.estimate(&key), cms.estimate(&key));
+        }
+    }
+
+    #[test]
+    fn test_memory_store_round_trip() {
+        assert_round_trip(&MemoryStore::default());
+    }
+
+    #[test]
+    fn test_file_store_round_trip() {
+        let path = std::env::temp_dir().join(format!("cms_store_test_{}.bin", std::process::id()));
+        let store = FileStore::new(&path);
+        assert_round_trip(&store);
+
+        // A second store replaces the first through a temporary file that does not linger
+        let mut cms = populated_sketch();
+        cms.increment("replacement");
+        store.store(&cms).expect("Store should succeed");
+        assert_eq!(store.load().expect("Load should succeed").to_bytes(), cms.to_bytes());
+        let mut tmp = path.clone().into_os_string();
+        tmp.push(".tmp");
+        assert!(!std::path::Path::new(&tmp).exists());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_merge_from_bytes_matches_merge() {
+        let shard = populated_sketch();
+        let mut streamed = populated_sketch();
+        let mut merged = populated_sketch();
+        streamed.increment("local_only");
+        merged.increment("local_only");
+
+        streamed.merge_from_bytes(&shard.to_bytes()).expect("Merge should succeed");
+        merged.merge(&shard).expect("Merge should succeed");
+        assert_eq!(streamed.to_bytes(), merged.to_bytes());
+
+        let mut foreign = CountMinSketch::with_seeds(
+            NonZeroUsize::new(1024).unwrap(),
+            NonZeroUsize::new(4).unwrap(),
+            [4, 3, 2, 1]
+        );
+        assert!(foreign.merge_from_bytes(&shard.to_bytes()).is_err());
+
+        let mut bytes = shard.to_bytes();
+        bytes.truncate(bytes.len() - 1);
+        assert!(streamed.merge_from_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_header_layout() {
+        let cms = populated_sketch();
+        let bytes = cms.to_bytes();
+
+        assert_eq!(SketchHeader::LEN, 72);
+        assert_eq!(bytes.len(), SketchHeader::LEN + 1024 * 4 * 8);
+        assert_eq!(&bytes[0..4], b"CMSK");
+        assert_eq!(u32::from_le_bytes(bytes[4..8].try_into().unwrap()), SketchHeader::VERSION);
+        assert_eq!(u64::from_le_bytes(bytes[8..16].try_into().unwrap()), 1024);
+        assert_eq!(u64::from_le_bytes(bytes[16..24].try_into().unwrap()), 4);
+        assert_eq!(u64::from_le_bytes(bytes[24..32].try_into().unwrap()), 1);
+        // AHash
+        assert_eq!(u64::from_le_bytes(bytes[56..64].try_into().unwrap()), 0);
+        // Row offset of an unsplit sketch
+        assert_eq!(u64::from_le_bytes(bytes[64..72].try_into().unwrap()), 0);
+    }
+
+    #[test]
+    fn test_reduce_from_readers() {
+        let shards: Vec<CountMinSketch> = (0..3)
+            .map(|s| {
+                let mut cms = populated_sketch();
+                for _ in 0..=s { cms.increment("shard_hit"); }
+                cms
+            })
+            .collect();
+        let serialized: Vec<Vec<u8>> = shards.iter().map(|s| s.to_bytes()).collect();
+
+        let reduced = CountMinSketch::reduce(serialized.iter().map(io::Cursor::new)).expect("Reduce should succeed");
+
+        let mut expected = populated_sketch();
+        expected.merge(&shards[1]).unwrap();
+        expected.merge(&shards[2]).unwrap();
+        expected.increment("shard_hit");
+        assert_eq!(reduced.to_bytes(), expected.to_bytes());
+        assert_eq!(reduced.estimate("shard_hit"), 1 + 2 + 3);
+        assert_eq!(reduced.estimate("key_0"), 3 * populated_sketch().estimate("key_0"));
+
+        let none: Vec<io::Cursor<Vec<u8>>> = Vec::new();
+        assert!(matches!(CountMinSketch::reduce(none), Err(e) if e.kind() == io::ErrorKind::InvalidInput));
+    }
+
+    #[test]
+    fn test_reduce_rejects_truncated_oversized_header() {
+        let mut bytes = populated_sketch().to_bytes();
+        // Valid power-of-two dimensions whose table could never be allocated
+        bytes[8..16].copy_from_slice(&(1u64 << 40).to_le_bytes());
+        bytes[16..24].copy_from_slice(&(1u64 << 20).to_le_bytes());
+
+        let reduced = CountMinSketch::reduce([io::Cursor::new(bytes)]);
+        assert!(matches!(reduced, Err(e) if e.kind() == io::ErrorKind::UnexpectedEof));
+    }
+
+    #[test]
+    fn test_bytes_preserve_hash_algo() {
+        let w = NonZeroUsize::new(256).unwrap();
+        let d = NonZeroUsize::new(4).unwrap();
+        let mut fx = CountMinSketch::with_hash_algo(w, d, [1, 2, 3, 4], HashAlgo::FxHash);
+        fx.increment("key");
+
+        let restored = CountMinSketch::from_bytes(&fx.to_bytes()).unwrap();
+        assert_eq!(restored.hash_algo(), HashAlgo::FxHash);
+        assert_eq!(restored.estimate("key"), 1);
+
+        // Same seeds, different algorithm
+        let mut ahash = CountMinSketch::with_seeds(w, d, [1, 2, 3, 4]);
+        assert!(ahash.merge_from_bytes(&fx.to_bytes()).is_err());
+
+        let mut bytes = fx.to_bytes();
+        bytes[56] = 9;
+        assert!(CountMinSketch::from_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_bytes_round_trip_estimates() {
+        let mut cms = CountMinSketch::with_seeds(NonZeroUsize::new(1000).unwrap(), NonZeroUsize::new(5).unwrap(), [11, 22, 33, 44]);
+        for i in 0..2_000u32 {
+            cms.increment(&(i % 97));
+        }
+        cms.increment_by("heavy", 1_000_000);
+
+        let restored = CountMinSketch::from_bytes(&cms.to_bytes()).expect("Valid buffer");
+        assert_eq!((restored.get_width(), restored.get_depth()), (1024, 8));
+        for key in 0..120u32 {
+            assert_eq!(restored.estimate(&key), cms.estimate(&key));
+        }
+        assert_eq!(restored.estimate("heavy"), cms.estimate("heavy"));
+        assert_eq!(restored.to_bytes(), cms.to_bytes());
+    }
+
+    #[test]
+    fn test_from_bytes_rejects_malformed_buffers() {
+        let bytes = populated_sketch().to_bytes();
+
+        assert_eq!(CountMinSketch::from_bytes(&[]).err(), Some(SketchError::TruncatedHeader));
+        assert_eq!(CountMinSketch::from_bytes(&bytes[..SketchHeader::LEN - 1]).err(), Some(SketchError::TruncatedHeader));
+        assert_eq!(CountMinSketch::from_bytes(&bytes[..bytes.len() - 8]).err(), Some(SketchError::TableLengthMismatch));
+        assert_eq!(CountMinSketch::from_bytes(&bytes[..bytes.len() - 3]).err(), Some(SketchError::TableLengthMismatch));
+
+        let mut extended = bytes.clone();
+        extended.extend_from_slice(&[0; 8]);
+        assert_eq!(CountMinSketch::from_bytes(&extended).err(), Some(SketchError::TableLengthMismatch));
+
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] ^= 0xFF;
+        assert_eq!(CountMinSketch::from_bytes(&bad_magic).err(), Some(SketchError::InvalidMagic));
+
+        let mut old_version = bytes.clone();
+        old_version[4..8].copy_from_slice(&1u32.to_le_bytes());
+        assert_eq!(CountMinSketch::from_bytes(&old_version).err(), Some(SketchError::UnsupportedVersion));
+
+        let mut bad_width = bytes;
+        bad_width[8..16].copy_from_slice(&1000u64.to_le_bytes());
+        assert_eq!(CountMinSketch::from_bytes(&bad_width).err(), Some(SketchError::InvalidDimensions));
+    }
+
+    #[test]
+    fn test_stream_round_trip() {
+        let cms = populated_sketch();
+        let mut cursor = io::Cursor::new(Vec::new());
+        cms.write_to(&mut cursor).expect("Write should succeed");
+        assert_eq!(cursor.get_ref(), &cms.to_bytes());
+
+        cursor.set_position(0);
+        let restored = CountMinSketch::read_from(&mut cursor).expect("Read should succeed");
+        assert_eq!(restored.to_bytes(), cms.to_bytes());
+        assert_eq!(restored.estimate("key_0"), cms.estimate("key_0"));
+        assert_eq!(cursor.position(), cursor.get_ref().len() as u64);
+
+        let mut truncated = io::Cursor::new(&cursor.get_ref()[..cursor.get_ref().len() - 1]);
+        assert!(matches!(CountMinSketch::read_from(&mut truncated), Err(e) if e.kind() == io::ErrorKind::UnexpectedEof));
+
+        // Valid power-of-two dimensions whose table could never be allocated
+        let mut oversized = cms.to_bytes();
+        oversized[8..16].copy_from_slice(&(1u64 << 40).to_le_bytes());
+        oversized[16..24].copy_from_slice(&(1u64 << 20).to_le_bytes());
+        let read = CountMinSketch::read_from(&mut io::Cursor::new(oversized));
+        assert!(matches!(read, Err(e) if e.kind() == io::ErrorKind::UnexpectedEof));
+    }
+}
\ No newline at end of file
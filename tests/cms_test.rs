@@ -1,7 +1,7 @@
 #[cfg(test)]
 mod tests {
 use std::num::NonZeroUsize;
-use count_min_sketch_rs::CountMinSketch;
+use count_min_sketch_rs::{ConcurrentCountMinSketch, CountMinSketch, DimAdjustment, DimensionWarning, FullEstimate, HashAlgo, RoundMode, SaturationError, SketchError, SketchHeader, SparsityReport};
 
     #[test]
     fn test_initialization() {
@@ -57,8 +57,14 @@ use count_min_sketch_rs::CountMinSketch;
     fn test_clear() {
         let mut cms = CountMinSketch::new(NonZeroUsize::try_from(1024usize).unwrap(),NonZeroUsize::try_from(8usize).unwrap());
         cms.increment("ghost");
+        cms.increment_by("other", 5);
         cms.clear();
         assert_eq!(cms.estimate("ghost"), 0);
+        assert_eq!(cms.estimate("other"), 0);
+        assert_eq!(cms.total(), 0);
+
+        cms.increment("ghost");
+        assert_eq!(cms.estimate("ghost"), 1);
     }
 
     #[test]
@@ -80,4 +86,1320 @@ use count_min_sketch_rs::CountMinSketch;
         // ln(1 / 0.02) = 3.9 -> 4
         assert_eq!(cms.get_depth(), 4);
     }
+
+    #[test]
+    fn test_checked_increment_by_rejects_overflow() {
+        let mut cms = CountMinSketch::new(NonZeroUsize::try_from(1024usize).unwrap(),NonZeroUsize::try_from(8usize).unwrap());
+        let key = "ledger";
+
+        cms.checked_increment_by(key, u64::MAX - 10).expect("Weight fits in every cell");
+        assert_eq!(cms.estimate(key), u64::MAX - 10);
+
+        // Pushing any cell past u64::MAX must fail without touching the table
+        assert_eq!(cms.checked_increment_by(key, 11), Err(SaturationError));
+        assert_eq!(cms.estimate(key), u64::MAX - 10);
+
+        cms.checked_increment_by(key, 10).expect("Exactly u64::MAX is still representable");
+        assert_eq!(cms.estimate(key), u64::MAX);
+    }
+
+    #[test]
+    fn test_validate_dimensions() {
+        assert_eq!(CountMinSketch::validate_dimensions(1024, 8), Ok(()));
+        assert_eq!(
+            CountMinSketch::validate_dimensions(4096, 1000),
+            Err(DimensionWarning::DepthTooLarge { depth: 1000, max: CountMinSketch::MAX_SENSIBLE_DEPTH })
+        );
+        assert_eq!(
+            CountMinSketch::validate_dimensions(16, 32),
+            Err(DimensionWarning::DepthExceedsWidth { width: 16, depth: 32 })
+        );
+    }
+
+    #[test]
+    fn test_estimate_early_exit_keeps_exact_counts() {
+        let mut cms = CountMinSketch::new(NonZeroUsize::try_from(1 << 16).unwrap(),NonZeroUsize::try_from(16usize).unwrap());
+        for count in 1..=50u64 {
+            let key = format!("present_{}", count);
+            for _ in 0..count {
+                cms.increment(&key);
+            }
+        }
+
+        for count in 1..=50u64 {
+            assert_eq!(cms.estimate(&format!("present_{}", count)), count);
+        }
+        for i in 0..50 {
+            assert_eq!(cms.estimate(&format!("absent_{}", i)), 0);
+        }
+    }
+
+    #[test]
+    fn test_merge_or_adopt() {
+        let shard = || {
+            let mut cms = CountMinSketch::new(NonZeroUsize::try_from(1024usize).unwrap(),NonZeroUsize::try_from(8usize).unwrap());
+            for _ in 0..5 { cms.increment("a"); }
+            cms.increment("b");
+            cms
+        };
+
+        // An empty accumulator takes over the donor's table, and must match a regular merge
+        let mut adopted = CountMinSketch::new(NonZeroUsize::try_from(1024usize).unwrap(),NonZeroUsize::try_from(8usize).unwrap());
+        let donor = shard();
+        let donor_table = donor.rows().next().unwrap().as_ptr();
+        adopted.merge_or_adopt(donor).expect("Adopt should succeed");
+        assert_eq!(adopted.rows().next().unwrap().as_ptr(), donor_table);
+        let mut merged = CountMinSketch::new(NonZeroUsize::try_from(1024usize).unwrap(),NonZeroUsize::try_from(8usize).unwrap());
+        merged.merge(&shard()).expect("Merge should succeed");
+        for key in ["a", "b", "c"] {
+            assert_eq!(adopted.estimate(key), merged.estimate(key));
+        }
+        assert!(adopted == merged);
+
+        // A non-empty accumulator keeps its own table and falls back to additive merge
+        let own_table = adopted.rows().next().unwrap().as_ptr();
+        adopted.merge_or_adopt(shard()).expect("Merge should succeed");
+        assert_eq!(adopted.rows().next().unwrap().as_ptr(), own_table);
+        assert_eq!(adopted.estimate("a"), 10);
+        assert_eq!(adopted.estimate("b"), 2);
+        assert_eq!(adopted.total(), 12);
+
+        let mut narrow = CountMinSketch::new(NonZeroUsize::try_from(512usize).unwrap(),NonZeroUsize::try_from(8usize).unwrap());
+        assert!(narrow.merge_or_adopt(shard()).is_err());
+        let mut reseeded = CountMinSketch::with_seeds(NonZeroUsize::try_from(1024usize).unwrap(),NonZeroUsize::try_from(8usize).unwrap(), [5, 6, 7, 8]);
+        assert_eq!(reseeded.merge_or_adopt(shard()).err(), Some(SketchError::IncompatibleSeeds));
+
+        // A zero total does not mean an empty table: counts outside the first row are kept
+        let mut written = CountMinSketch::new(NonZeroUsize::try_from(1024usize).unwrap(),NonZeroUsize::try_from(8usize).unwrap());
+        written.set_cell(1024 + 3, 7);
+        assert_eq!(written.total(), 0);
+        let own_table = written.rows().next().unwrap().as_ptr();
+        written.merge_or_adopt(shard()).expect("Merge should succeed");
+        assert_eq!(written.rows().next().unwrap().as_ptr(), own_table);
+        assert_eq!(written.rows().nth(1).unwrap()[3], 7 + shard().rows().nth(1).unwrap()[3]);
+    }
+
+    #[test]
+    fn test_estimate_with_confidence() {
+        let mut cms = CountMinSketch::new(NonZeroUsize::try_from(1024usize).unwrap(),NonZeroUsize::try_from(8usize).unwrap());
+        for _ in 0..10 { cms.increment("isolated"); }
+        assert_eq!(cms.estimate_with_confidence("isolated"), (10, 1.0));
+        assert_eq!(cms.estimate_with_confidence("absent"), (0, 0.0));
+
+        // A tiny table forces every key to collide differently in each row
+        let mut crowded = CountMinSketch::new(NonZeroUsize::try_from(8usize).unwrap(),NonZeroUsize::try_from(8usize).unwrap());
+        for i in 0..1000 { crowded.increment(&i); }
+        let (estimate, confidence) = crowded.estimate_with_confidence(&0);
+        assert_eq!(estimate, crowded.estimate(&0));
+        assert!(confidence < 1.0, "Collided key should not be fully confident, got {}", confidence);
+    }
+
+    #[test]
+    fn test_with_seed() {
+        let mut cms1 = CountMinSketch::with_seed(NonZeroUsize::try_from(1024usize).unwrap(), NonZeroUsize::try_from(8usize).unwrap(), 42);
+        let mut cms2 = CountMinSketch::with_seed(NonZeroUsize::try_from(1024usize).unwrap(), NonZeroUsize::try_from(8usize).unwrap(), 42);
+
+        for i in 0..100 {
+            cms1.increment(&i);
+            cms2.increment(&i);
+        }
+        // Same seed means the same hash network, so both tables hold identical counts
+        assert_eq!(cms1.l1_distance(&cms2).unwrap(), 0);
+
+        cms1.merge(&cms2).expect("Merge should succeed");
+        for i in 0..100 {
+            assert_eq!(cms1.estimate(&i), 2 * cms2.estimate(&i));
+        }
+    }
+
+    fn _assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_send_sync() {
+        // Fails to compile if a future field makes the sketch thread-unsafe
+        _assert_send_sync::<CountMinSketch>();
+    }
+
+    #[test]
+    fn test_merge_weighted() {
+        let mut source = CountMinSketch::new(NonZeroUsize::try_from(1024usize).unwrap(),NonZeroUsize::try_from(8usize).unwrap());
+        for _ in 0..7 { source.increment("sampled"); }
+
+        let mut once = CountMinSketch::new(NonZeroUsize::try_from(1024usize).unwrap(),NonZeroUsize::try_from(8usize).unwrap());
+        let mut twice = CountMinSketch::new(NonZeroUsize::try_from(1024usize).unwrap(),NonZeroUsize::try_from(8usize).unwrap());
+        once.increment("sampled");
+        twice.increment("sampled");
+
+        once.merge_weighted(&source, 1.0).expect("Merge should succeed");
+        twice.merge_weighted(&source, 2.0).expect("Merge should succeed");
+
+        assert_eq!(once.estimate("sampled"), 1 + 7);
+        assert_eq!(twice.estimate("sampled"), 1 + 14);
+    }
+
+    #[test]
+    fn test_estimate_dedup() {
+        let mut cms = CountMinSketch::new(NonZeroUsize::try_from(1024usize).unwrap(),NonZeroUsize::try_from(4usize).unwrap());
+        for _ in 0..3 { cms.increment("a"); }
+        cms.increment("b");
+
+        let estimates = cms.estimate_dedup(&["a", "a", "b", "a"]);
+        assert_eq!(estimates.len(), 2);
+        assert_eq!(estimates["a"], 3);
+        assert_eq!(estimates["b"], 1);
+        assert!(cms.estimate_dedup::<&str>(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_merge_capped() {
+        let mut merged = CountMinSketch::new(NonZeroUsize::try_from(1024usize).unwrap(),NonZeroUsize::try_from(4usize).unwrap());
+        for _ in 0..4 {
+            let mut shard = CountMinSketch::new(NonZeroUsize::try_from(1024usize).unwrap(),NonZeroUsize::try_from(4usize).unwrap());
+            for _ in 0..30 { shard.increment("frequent"); }
+            shard.increment("rare");
+            merged.merge_capped(&shard, 100).expect("Merge should succeed");
+        }
+
+        assert_eq!(merged.estimate("frequent"), 100);
+        assert_eq!(merged.estimate("rare"), 4);
+
+        let other = CountMinSketch::new(NonZeroUsize::try_from(512usize).unwrap(),NonZeroUsize::try_from(4usize).unwrap());
+        assert!(merged.merge_capped(&other, 100).is_err());
+    }
+
+    #[test]
+    fn test_increment_many_matches_increment() {
+        let mut per_call = CountMinSketch::new(NonZeroUsize::try_from(1024usize).unwrap(),NonZeroUsize::try_from(8usize).unwrap());
+        let mut batched = CountMinSketch::new(NonZeroUsize::try_from(1024usize).unwrap(),NonZeroUsize::try_from(8usize).unwrap());
+        let items: Vec<String> = (0..500).map(|i| format!("key_{}", i % 37)).collect();
+
+        for item in &items {
+            per_call.increment(item);
+        }
+        batched.increment_many(&items);
+
+        assert_eq!(per_call.l1_distance(&batched).unwrap(), 0);
+        for i in 0..37 {
+            let key = format!("key_{}", i);
+            assert_eq!(batched.estimate(&key), per_call.estimate(&key));
+        }
+    }
+
+    #[test]
+    fn test_new_reporting() {
+        let (cms, adjustment) = CountMinSketch::new_reporting(NonZeroUsize::try_from(1000usize).unwrap(),NonZeroUsize::try_from(5usize).unwrap());
+        assert_eq!(adjustment, DimAdjustment {
+            requested_width: 1000,
+            actual_width: 1024,
+            requested_depth: 5,
+            actual_depth: 8,
+        });
+        assert_eq!(cms.get_width(), adjustment.actual_width);
+        assert_eq!(cms.get_depth(), adjustment.actual_depth);
+    }
+
+    #[test]
+    fn test_absorb_keys() {
+        let width = NonZeroUsize::try_from(1024usize).unwrap();
+        let depth = NonZeroUsize::try_from(8usize).unwrap();
+        let key_log = [("alpha", 12u64), ("beta", 3), ("gamma", 40)];
+
+        // Differently seeded sketch: only its key log can be merged into the target
+        let mut foreign = CountMinSketch::with_seed(width, depth, 7);
+        let mut native = CountMinSketch::new(width, depth);
+        for (key, count) in key_log {
+            for _ in 0..count {
+                foreign.increment(key);
+                native.increment(key);
+            }
+        }
+
+        let mut absorbed = CountMinSketch::new(width, depth);
+        let mut merged = CountMinSketch::new(width, depth);
+        absorbed.increment("alpha");
+        merged.increment("alpha");
+
+        absorbed.absorb_keys(&key_log);
+        merged.merge(&native).expect("Merge should succeed");
+
+        assert_eq!(absorbed.l1_distance(&merged).unwrap(), 0);
+        for (key, count) in key_log {
+            assert_eq!(absorbed.estimate(key), merged.estimate(key));
+            assert!(foreign.estimate(key) >= count);
+        }
+    }
+
+    #[test]
+    fn test_for_membership_false_positive_rate() {
+        let expected_items = 10_000;
+        let target = 0.01;
+        let mut cms = CountMinSketch::for_membership(expected_items, target);
+
+        for i in 0..expected_items {
+            cms.increment(&format!("member_{}", i));
+        }
+        for i in 0..expected_items {
+            assert!(cms.contains(&format!("member_{}", i)));
+        }
+
+        let probes = 100_000;
+        let false_positives = (0..probes)
+            .filter(|i| cms.contains(&format!("stranger_{}", i)))
+            .count();
+        let empirical = false_positives as f64 / probes as f64;
+        assert!(empirical <= target, "Empirical FP rate {} exceeds target {}", empirical, target);
+    }
+
+    #[test]
+    fn test_rank() {
+        let mut cms = CountMinSketch::new(NonZeroUsize::try_from(1024usize).unwrap(),NonZeroUsize::try_from(8usize).unwrap());
+        let counts = [("gold", 50), ("silver", 30), ("bronze", 20), ("wood", 5)];
+        for (key, count) in counts {
+            for _ in 0..count {
+                cms.increment(key);
+            }
+        }
+        let candidates = ["gold", "silver", "bronze", "wood"];
+
+        assert_eq!(cms.rank(&"gold", &candidates), 0);
+        assert_eq!(cms.rank(&"bronze", &candidates), 2);
+        assert_eq!(cms.rank(&"wood", &candidates), 3);
+        assert_eq!(cms.rank(&"absent", &candidates), 4);
+    }
+
+    #[test]
+    fn test_changed_cells_replication() {
+        let mut baseline = CountMinSketch::new(NonZeroUsize::try_from(1024usize).unwrap(),NonZeroUsize::try_from(8usize).unwrap());
+        for i in 0..100 { baseline.increment(&i); }
+
+        let mut replica = CountMinSketch::from_bytes(&baseline.to_bytes()).unwrap();
+        let mut current = CountMinSketch::from_bytes(&baseline.to_bytes()).unwrap();
+        for i in 50..150 { current.increment(&i); }
+
+        let diffs: Vec<(usize, u64)> = current.changed_cells(&baseline).unwrap().collect();
+        assert!(!diffs.is_empty() && diffs.len() <= 100 * 8);
+        for (index, value) in diffs {
+            replica.set_cell(index, value);
+        }
+
+        assert_eq!(replica.to_bytes(), current.to_bytes());
+        assert_eq!(current.changed_cells(&replica).unwrap().count(), 0);
+
+        let narrow = CountMinSketch::new(NonZeroUsize::try_from(512usize).unwrap(),NonZeroUsize::try_from(8usize).unwrap());
+        assert!(current.changed_cells(&narrow).is_err());
+    }
+
+    #[test]
+    fn test_for_budget() {
+        let budget = 1_048_576;
+        let cms = CountMinSketch::for_budget(budget, 8);
+        let table_bytes = |w: usize| w * cms.get_depth() * std::mem::size_of::<u64>();
+
+        assert_eq!(cms.get_depth(), 8);
+        assert_eq!(cms.get_width(), 16384);
+        assert!(table_bytes(cms.get_width()) <= budget);
+        // The next power of two would not fit
+        assert!(table_bytes(cms.get_width() * 2) > budget);
+
+        // Budgets between powers of two round down
+        assert_eq!(CountMinSketch::for_budget(budget + budget / 2, 8).get_width(), 16384);
+    }
+
+    #[test]
+    #[cfg(not(target_feature = "aes"))]
+    fn test_portable_hash_cell_indices() {
+        // Known answer for the portable (non-AES) ahash build: any machine compiling for the
+        // default target must place this key in exactly these cells.
+        let mut cms = CountMinSketch::with_seeds(NonZeroUsize::new(1024).unwrap(), NonZeroUsize::new(4).unwrap(), [1, 2, 3, 4]);
+        let empty = CountMinSketch::with_seeds(NonZeroUsize::new(1024).unwrap(), NonZeroUsize::new(4).unwrap(), [1, 2, 3, 4]);
+        cms.increment("portable");
+
+        let cells: Vec<usize> = cms.changed_cells(&empty).unwrap().map(|(index, _)| index).collect();
+        assert_eq!(cells, [464, 1147, 2854, 3537]);
+    }
+
+    #[test]
+    fn test_error_vs() {
+        let mut cms = CountMinSketch::new(NonZeroUsize::try_from(64usize).unwrap(),NonZeroUsize::try_from(4usize).unwrap());
+        for i in 0..500u64 {
+            for _ in 0..(i % 5) {
+                cms.increment(&i);
+            }
+        }
+        for i in 0..500u64 {
+            assert!(cms.error_vs(&i, i % 5) >= 0);
+        }
+
+        // Wiping the key's first cell simulates a broken removal: the estimate drops below the truth
+        let key = 7u64;
+        let empty = CountMinSketch::new(NonZeroUsize::try_from(64usize).unwrap(),NonZeroUsize::try_from(4usize).unwrap());
+        let mut single = CountMinSketch::new(NonZeroUsize::try_from(64usize).unwrap(),NonZeroUsize::try_from(4usize).unwrap());
+        single.increment(&key);
+        let (first_cell, _) = single.changed_cells(&empty).unwrap().next().unwrap();
+        cms.set_cell(first_cell, 0);
+        assert_eq!(cms.error_vs(&key, 2), -2);
+
+        assert_eq!(empty.error_vs(&key, u64::MAX), i64::MIN);
+    }
+
+    #[test]
+    fn test_from_map() {
+        let exact: std::collections::HashMap<String, u64> = (0..2_000u64)
+            .map(|i| (format!("user_{}", i), i % 50 + 1))
+            .collect();
+        let mass: u64 = exact.values().sum();
+        let epsilon = 0.001;
+
+        let cms = CountMinSketch::from_map(&exact, epsilon, 0.01);
+        let bound = (epsilon * mass as f64).ceil() as u64;
+
+        for (key, &count) in &exact {
+            let est = cms.estimate(key);
+            assert!(est >= count);
+            assert!(est - count <= bound, "Error {} exceeds epsilon * N = {}", est - count, bound);
+        }
+        // epsilon * N is well above one count, so the sizing is plain with_params
+        let reference = CountMinSketch::with_params(epsilon, 0.01);
+        assert_eq!((cms.get_width(), cms.get_depth()), (reference.get_width(), reference.get_depth()));
+    }
+
+    #[test]
+    fn test_from_map_scales_epsilon_to_mass() {
+        // N = 100: epsilon = 1e-4 would ask for e / 1e-4 columns, but 1 / N already bounds
+        // the error below one count
+        let exact: std::collections::HashMap<u64, u64> = (0..10u64).map(|i| (i, 10)).collect();
+        let cms = CountMinSketch::from_map(&exact, 0.0001, 0.01);
+
+        assert_eq!(cms.get_width(), 512); // (e * 100).ceil() = 272, rounded up
+        assert_eq!(cms.get_depth(), CountMinSketch::with_params(0.0001, 0.01).get_depth());
+        for (key, &count) in &exact {
+            assert_eq!(cms.estimate(key), count);
+        }
+    }
+
+    #[test]
+    fn test_sparsity_report() {
+        // 4 x 4 = 16 cells
+        let mut cms = CountMinSketch::new(NonZeroUsize::try_from(4usize).unwrap(),NonZeroUsize::try_from(4usize).unwrap());
+        assert_eq!(cms.sparsity_report(), SparsityReport {
+            zero_cells: 16,
+            nonzero_cells: 0,
+            longest_zero_run: 16,
+            distinct_values: 1,
+        });
+
+        // [0 5 0 0 | 0 0 0 0 | 0 5 0 9 | 0 0 0 1]
+        for (index, value) in [(1, 5), (9, 5), (11, 9), (15, 1)] {
+            cms.set_cell(index, value);
+        }
+        assert_eq!(cms.sparsity_report(), SparsityReport {
+            zero_cells: 12,
+            nonzero_cells: 4,
+            longest_zero_run: 7,
+            distinct_values: 4,
+        });
+    }
+
+    #[test]
+    fn test_depth_one_fast_path() {
+        let w = NonZeroUsize::try_from(256usize).unwrap();
+        let seeds = [11, 22, 33, 44];
+        let mut single = CountMinSketch::with_seeds(w, NonZeroUsize::try_from(1usize).unwrap(), seeds);
+        let mut double = CountMinSketch::with_seeds(w, NonZeroUsize::try_from(2usize).unwrap(), seeds);
+
+        for i in 0..1_000u32 {
+            for _ in 0..(i % 5) {
+                single.increment(&i);
+                double.increment(&i);
+            }
+        }
+
+        // Every estimate is the item's single cell, so it never undercounts
+        for i in 0..1_000u32 {
+            assert!(single.estimate(&i) >= (i % 5) as u64);
+        }
+
+        // The fast path places items exactly where row 0 of the generic path does
+        let single_bytes = single.to_bytes();
+        let double_bytes = double.to_bytes();
+        let row_bytes = 256 * 8;
+        assert_eq!(
+            &single_bytes[SketchHeader::LEN..],
+            &double_bytes[SketchHeader::LEN..SketchHeader::LEN + row_bytes]
+        );
+    }
+
+    #[test]
+    fn test_trend_across_windows() {
+        let w = NonZeroUsize::try_from(1024usize).unwrap();
+        let d = NonZeroUsize::try_from(4usize).unwrap();
+        let mut windows: Vec<CountMinSketch> = (0..3).map(|_| CountMinSketch::new(w, d)).collect();
+
+        for (i, window) in windows.iter_mut().enumerate() {
+            for _ in 0..(i + 1) * 10 {
+                window.increment("rising");
+            }
+            window.increment("steady");
+        }
+
+        let refs: Vec<&CountMinSketch> = windows.iter().collect();
+        let series = CountMinSketch::trend(&refs, "rising");
+        assert_eq!(series.len(), 3);
+        assert!(series.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(CountMinSketch::trend(&[], "rising").is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "same seeds")]
+    fn test_trend_rejects_mixed_seeds() {
+        let w = NonZeroUsize::try_from(64usize).unwrap();
+        let d = NonZeroUsize::try_from(2usize).unwrap();
+        let a = CountMinSketch::with_seeds(w, d, [1, 2, 3, 4]);
+        let b = CountMinSketch::with_seeds(w, d, [5, 6, 7, 8]);
+        CountMinSketch::trend(&[&a, &b], "key");
+    }
+
+    #[test]
+    fn test_hash_algos_count_and_merge() {
+        let w = NonZeroUsize::try_from(1024usize).unwrap();
+        let d = NonZeroUsize::try_from(4usize).unwrap();
+        let seeds = [7, 8, 9, 10];
+
+        for algo in [HashAlgo::AHash, HashAlgo::FxHash, HashAlgo::SipHash] {
+            let mut a = CountMinSketch::with_hash_algo(w, d, seeds, algo);
+            let mut b = CountMinSketch::with_hash_algo(w, d, seeds, algo);
+            assert_eq!(a.hash_algo(), algo);
+
+            for i in 0..100u64 {
+                a.increment(&i);
+                a.increment(&format!("key-{i}"));
+            }
+            b.increment_many(["key-0", "key-0"].iter().copied());
+
+            for i in 0..100u64 {
+                assert!(a.estimate(&i) >= 1, "{algo:?} lost integer key {i}");
+            }
+            // Few collisions are expected with 200 keys over 1024 columns
+            let exact = (0..100u64).filter(|i| a.estimate(i) == 1).count();
+            assert!(exact > 90, "{algo:?} collides too often: {exact}");
+
+            a.merge(&b).unwrap();
+            assert!(a.estimate("key-0") >= 3);
+        }
+
+        assert_eq!(CountMinSketch::new(w, d).hash_algo(), HashAlgo::AHash);
+    }
+
+    #[test]
+    fn test_merge_reports_incompatible_dimensions() {
+        let mut narrow = CountMinSketch::new(NonZeroUsize::try_from(64usize).unwrap(), NonZeroUsize::try_from(2usize).unwrap());
+        let wide = CountMinSketch::new(NonZeroUsize::try_from(128usize).unwrap(), NonZeroUsize::try_from(4usize).unwrap());
+        match narrow.merge(&wide) {
+            Err(SketchError::IncompatibleDimensions { left, right }) => {
+                assert_eq!(left, (64, 2));
+                assert_eq!(right, (128, 4));
+            }
+            other => panic!("Expected IncompatibleDimensions, got {:?}", other),
+        }
+        let err = narrow.l1_distance(&wide).unwrap_err();
+        assert_eq!(err.to_string(), "Incompatible dimensions: 64x2 and 128x4");
+    }
+
+    #[test]
+    fn test_merge_rejects_different_seeds() {
+        let w = NonZeroUsize::try_from(64usize).unwrap();
+        let d = NonZeroUsize::try_from(2usize).unwrap();
+        let mut a = CountMinSketch::with_seeds(w, d, [1, 2, 3, 4]);
+        let b = CountMinSketch::with_seeds(w, d, [5, 6, 7, 8]);
+        assert_eq!(a.merge(&b), Err(SketchError::IncompatibleSeeds));
+        assert_eq!(a.l1_distance(&b), Err(SketchError::IncompatibleSeeds));
+        assert_eq!(a.cosine_similarity(&b), Err(SketchError::IncompatibleSeeds));
+    }
+
+    #[test]
+    fn test_merge_rejects_mixed_hash_algos() {
+        let w = NonZeroUsize::try_from(64usize).unwrap();
+        let d = NonZeroUsize::try_from(2usize).unwrap();
+        let mut fx = CountMinSketch::with_hash_algo(w, d, [1, 2, 3, 4], HashAlgo::FxHash);
+        let sip = CountMinSketch::with_hash_algo(w, d, [1, 2, 3, 4], HashAlgo::SipHash);
+        assert_eq!(fx.merge(&sip), Err(SketchError::IncompatibleHashAlgorithm));
+        assert!(fx.merge_weighted(&sip, 1.0).is_err());
+    }
+
+    #[test]
+    fn test_empirical_delta_within_bound() {
+        let mut cms = CountMinSketch::with_params(0.01, 0.01);
+        let mut truth = std::collections::HashMap::new();
+        for k in 0..5_000u64 {
+            // Heavy head, long tail
+            let count = 1 + 1_000 / (k + 1);
+            for _ in 0..count {
+                cms.increment(&k);
+            }
+            truth.insert(k, count);
+        }
+
+        let total: u64 = truth.values().sum();
+        let slack = (std::f64::consts::E / cms.get_width() as f64 * total as f64).ceil() as u64;
+        let empirical = cms.empirical_delta(&truth, slack);
+        assert!(empirical <= cms.actual_delta(), "{empirical} > {}", cms.actual_delta());
+        assert!(cms.actual_delta() <= 0.01);
+
+        // Without slack, the 5000 keys over 512 columns do collide
+        assert!(cms.empirical_delta(&truth, 0) > 0.0);
+        assert_eq!(cms.empirical_delta(&std::collections::HashMap::<u64, u64>::new(), 0), 0.0);
+    }
+
+    #[test]
+    fn test_estimate_full_matches_estimators() {
+        let w = NonZeroUsize::try_from(256usize).unwrap();
+        let d = NonZeroUsize::try_from(4usize).unwrap();
+        let mut cms = CountMinSketch::new(w, d);
+        for i in 0..5_000u32 {
+            cms.increment(&(i % 700));
+        }
+        for _ in 0..100 {
+            cms.increment("hot");
+        }
+
+        for key in [0u32, 13, 699, 10_000] {
+            let full = cms.estimate_full(&key);
+            assert_eq!(full.min, cms.estimate(&key));
+            assert_eq!((full.min, full.confidence), cms.estimate_with_confidence(&key));
+            assert!(full.mean_min <= full.min);
+            assert!(full.mean >= full.min as f64);
+        }
+
+        // The mean-min correction removes most of the collision noise of a heavily loaded sketch
+        let hot = cms.estimate_full("hot");
+        assert_eq!(hot.min, cms.estimate("hot"));
+        assert!(hot.mean_min.abs_diff(100) < hot.min.abs_diff(100) || hot.min == 100);
+
+        let empty = CountMinSketch::new(w, d).estimate_full("x");
+        assert_eq!(empty, FullEstimate { min: 0, mean_min: 0, mean: 0.0, confidence: 0.0 });
+    }
+
+    #[test]
+    fn test_with_seed_str() {
+        let w = NonZeroUsize::try_from(512usize).unwrap();
+        let d = NonZeroUsize::try_from(4usize).unwrap();
+        let mut a = CountMinSketch::with_seed_str(w, d, "cluster-secret");
+        let mut b = CountMinSketch::with_seed_str(w, d, "cluster-secret");
+        let mut other = CountMinSketch::with_seed_str(w, d, "cluster-secreT");
+
+        for i in 0..100u32 {
+            a.increment(&i);
+            b.increment(&i);
+            other.increment(&i);
+        }
+
+        // Same secret: identical tables, so the merge is a plain doubling
+        assert_eq!(a.to_bytes(), b.to_bytes());
+        a.merge(&b).unwrap();
+        for i in 0..100u32 {
+            assert_eq!(a.estimate(&i), 2 * b.estimate(&i));
+        }
+
+        // A one-character change yields a different hash network
+        assert_ne!(b.to_bytes(), other.to_bytes());
+        let empty_secret = CountMinSketch::with_seed_str(w, d, "");
+        assert_ne!(empty_secret.to_bytes(), CountMinSketch::with_seed_str(w, d, "\0").to_bytes());
+    }
+
+    #[test]
+    fn test_with_params_capped() {
+        // e / 1e-6 needs 4M columns; a 3000 cap floors to 2048
+        let (cms, capped) = CountMinSketch::with_params_capped(1e-6, 0.01, 3_000);
+        assert!(capped);
+        assert_eq!(cms.get_width(), 2048);
+        assert!(cms.actual_epsilon() > 1e-6);
+        assert_eq!(cms.get_depth(), CountMinSketch::with_params(1e-6, 0.01).get_depth());
+
+        let (cms, capped) = CountMinSketch::with_params_capped(0.01, 0.01, 1 << 20);
+        assert!(!capped);
+        assert_eq!(cms.get_width(), CountMinSketch::with_params(0.01, 0.01).get_width());
+        assert!(cms.actual_epsilon() <= 0.01);
+    }
+
+    #[test]
+    fn test_collision_probability() {
+        let wide = CountMinSketch::new(NonZeroUsize::try_from(1usize << 16).unwrap(), NonZeroUsize::try_from(8usize).unwrap());
+        assert_eq!(wide.collision_probability("alpha", "alpha"), 1.0);
+        assert_eq!(wide.collision_probability("alpha", "beta"), 0.0);
+
+        // A single column forces every key into the same cell
+        let narrow = CountMinSketch::new(NonZeroUsize::try_from(1usize).unwrap(), NonZeroUsize::try_from(4usize).unwrap());
+        assert_eq!(narrow.collision_probability("alpha", "beta"), 1.0);
+
+        // With 4 columns, some pairs collide in only part of the rows
+        let tiny = CountMinSketch::new(NonZeroUsize::try_from(4usize).unwrap(), NonZeroUsize::try_from(8usize).unwrap());
+        let partial = (0..100u32).map(|i| tiny.collision_probability(&i, &(i + 1))).any(|p| p > 0.0 && p < 1.0);
+        assert!(partial);
+    }
+
+    #[test]
+    fn test_split_and_merge_rows() {
+        let w = NonZeroUsize::try_from(256usize).unwrap();
+        let d = NonZeroUsize::try_from(8usize).unwrap();
+        let mut cms = CountMinSketch::new(w, d);
+        for i in 0..3_000u32 {
+            cms.increment(&(i % 400));
+        }
+
+        let (upper, lower) = cms.split_rows(3).unwrap();
+        assert_eq!((upper.get_depth(), lower.get_depth()), (3, 5));
+        for i in 0..400u32 {
+            // Each half is a shallower but valid sketch
+            assert!(upper.estimate(&i) >= cms.estimate(&i));
+            assert!(lower.estimate(&i) >= cms.estimate(&i));
+            assert!(lower.estimate(&i) >= 7);
+        }
+
+        // Halves survive serialization and reassemble into the original
+        let lower = CountMinSketch::from_bytes(&lower.to_bytes()).unwrap();
+        let whole = CountMinSketch::merge_rows(&upper, &lower).unwrap();
+        assert_eq!(whole.to_bytes(), cms.to_bytes());
+        for i in 0..500u32 {
+            assert_eq!(whole.estimate(&i), cms.estimate(&i));
+        }
+
+        assert!(CountMinSketch::merge_rows(&lower, &upper).is_err());
+        assert!(cms.split_rows(0).is_err());
+        assert!(cms.split_rows(8).is_err());
+
+        // Halves holding different rows must not be merged cell by cell
+        let (mut first, _) = cms.split_rows(4).unwrap();
+        let (_, second) = cms.split_rows(4).unwrap();
+        assert!(first.merge(&second).is_err());
+
+        // Lower halves hashing keys differently are rejected with the matching error
+        let (_, fx_lower) = CountMinSketch::with_hash_algo(w, d, cms.get_seeds(), HashAlgo::FxHash).split_rows(3).unwrap();
+        assert_eq!(CountMinSketch::merge_rows(&upper, &fx_lower).err(), Some(SketchError::IncompatibleHashAlgorithm));
+        let (_, reseeded_lower) = CountMinSketch::with_seeds(w, d, [5, 6, 7, 8]).split_rows(3).unwrap();
+        assert_eq!(CountMinSketch::merge_rows(&upper, &reseeded_lower).err(), Some(SketchError::IncompatibleSeeds));
+    }
+
+    #[test]
+    fn test_increment_by() {
+        let w = NonZeroUsize::try_from(4096usize).unwrap();
+        let d = NonZeroUsize::try_from(4usize).unwrap();
+        let mut cms = CountMinSketch::new(w, d);
+        cms.increment_by("bulk", 1000);
+        assert_eq!(cms.estimate("bulk"), 1000);
+
+        // Same cells as repeated increments
+        let mut looped = CountMinSketch::new(w, d);
+        for _ in 0..1000 {
+            looped.increment("bulk");
+        }
+        assert_eq!(cms.to_bytes(), looped.to_bytes());
+
+        cms.increment_by("zero", 0);
+        assert_eq!(cms.estimate("zero"), 0);
+    }
+
+    #[test]
+    fn test_increment_by_saturates() {
+        let mut cms = CountMinSketch::new(NonZeroUsize::try_from(64usize).unwrap(), NonZeroUsize::try_from(4usize).unwrap());
+        cms.increment_by("key", u64::MAX);
+        cms.increment_by("key", 5);
+        assert_eq!(cms.estimate("key"), u64::MAX);
+    }
+
+    #[test]
+    fn test_apply_hash_counts() {
+        let w = NonZeroUsize::try_from(512usize).unwrap();
+        let d = NonZeroUsize::try_from(4usize).unwrap();
+        let mut direct = CountMinSketch::new(w, d);
+        let mut prehashed = CountMinSketch::new(w, d);
+
+        // Upstream aggregates keys into sorted (hash, count) pairs
+        let mut pairs: Vec<(u64, u64)> = (0..300u32)
+            .map(|k| (prehashed.hash_of(&k), (k % 7 + 1) as u64))
+            .collect();
+        pairs.sort_unstable();
+        for k in 0..300u32 {
+            direct.increment_by(&k, (k % 7 + 1) as u64);
+        }
+
+        prehashed.apply_hash_counts(&pairs);
+        for k in 0..300u32 {
+            assert_eq!(prehashed.estimate(&k), direct.estimate(&k));
+        }
+        assert_eq!(prehashed.to_bytes(), direct.to_bytes());
+
+        prehashed.increment_hash(prehashed.hash_of("single"), 3);
+        assert!(prehashed.estimate("single") >= 3);
+    }
+
+    #[test]
+    fn test_get_seeds() {
+        let w = NonZeroUsize::try_from(64usize).unwrap();
+        let d = NonZeroUsize::try_from(2usize).unwrap();
+        assert_eq!(CountMinSketch::with_seeds(w, d, [5, 6, 7, 8]).get_seeds(), [5, 6, 7, 8]);
+        assert_eq!(CountMinSketch::new(w, d).get_seeds(), [2025, 2, 18, 2118]);
+        assert_eq!(CountMinSketch::with_params(0.01, 0.01).get_seeds(), [2025, 2, 18, 2118]);
+
+        // Sketches sharing seeds are interchangeable shards
+        let a = CountMinSketch::with_seed(w, d, 99);
+        let b = CountMinSketch::with_seeds(w, d, a.get_seeds());
+        assert_eq!(a.to_bytes(), b.to_bytes());
+    }
+
+    #[test]
+    fn test_with_params_rounded() {
+        // e / epsilon = 512.5, so the computed width is 513
+        let epsilon = std::f64::consts::E / 512.5;
+        let up = CountMinSketch::with_params_rounded(epsilon, 0.01, RoundMode::Up);
+        let nearest = CountMinSketch::with_params_rounded(epsilon, 0.01, RoundMode::Nearest);
+        let down = CountMinSketch::with_params_rounded(epsilon, 0.01, RoundMode::Down);
+        assert_eq!(up.get_width(), 1024);
+        assert_eq!(nearest.get_width(), 512);
+        assert_eq!(down.get_width(), 512);
+        assert_eq!(up.get_width(), CountMinSketch::with_params(epsilon, 0.01).get_width());
+
+        // The memory saving costs accuracy
+        assert!(up.actual_epsilon() <= epsilon);
+        assert!(nearest.actual_epsilon() > epsilon && nearest.actual_epsilon() < 1.5 * epsilon);
+
+        // e / epsilon = 1000: closer to 1024 than to 512
+        let epsilon = std::f64::consts::E / 1000.0;
+        assert_eq!(CountMinSketch::with_params_rounded(epsilon, 0.01, RoundMode::Nearest).get_width(), 1024);
+        assert_eq!(CountMinSketch::with_params_rounded(epsilon, 0.01, RoundMode::Down).get_width(), 512);
+    }
+
+    #[test]
+    fn test_conservative_update_on_zipf() {
+        use rand::prelude::*;
+        use rand_distr::{Distribution, Zipf};
+
+        let w = NonZeroUsize::try_from(256usize).unwrap();
+        let d = NonZeroUsize::try_from(4usize).unwrap();
+        let mut standard = CountMinSketch::with_seeds(w, d, [1, 2, 3, 4]);
+        let mut conservative = CountMinSketch::with_seeds(w, d, [1, 2, 3, 4]);
+        let mut truth = std::collections::HashMap::new();
+
+        let mut rng = StdRng::seed_from_u64(7);
+        let zipf = Zipf::new(10_000.0, 1.1).unwrap();
+        for _ in 0..50_000 {
+            let key = zipf.sample(&mut rng) as u64;
+            standard.increment(&key);
+            conservative.increment_conservative(&key);
+            *truth.entry(key).or_insert(0u64) += 1;
+        }
+
+        let mut standard_error = 0.0;
+        let mut conservative_error = 0.0;
+        for (key, &actual) in &truth {
+            let (s, c) = (standard.estimate(key), conservative.estimate(key));
+            assert!(actual <= c && c <= s, "key {key}: {actual} <= {c} <= {s}");
+            standard_error += (s - actual) as f64 / actual as f64;
+            conservative_error += (c - actual) as f64 / actual as f64;
+        }
+        assert!(conservative_error < standard_error, "{conservative_error} >= {standard_error}");
+    }
+
+    #[test]
+    fn test_u8_counter_saturates() {
+        let w = NonZeroUsize::try_from(64usize).unwrap();
+        let d = NonZeroUsize::try_from(4usize).unwrap();
+        let mut cms = CountMinSketch::<u8>::with_counter(w, d);
+        for _ in 0..300 {
+            cms.increment("hot");
+        }
+        assert_eq!(cms.estimate("hot"), 255);
+
+        cms.increment_by("bulk", 1_000);
+        assert_eq!(cms.estimate("bulk"), 255);
+
+        let mut a = CountMinSketch::<u8>::with_counter(w, d);
+        let mut b = CountMinSketch::<u8>::with_counter(w, d);
+        a.increment_by("warm", 200);
+        b.increment_by("warm", 200);
+        a.merge(&b).expect("Merge should succeed");
+        assert_eq!(a.estimate("warm"), 255);
+    }
+
+    #[test]
+    fn test_narrow_counter_hashes_like_default() {
+        let w = NonZeroUsize::try_from(256usize).unwrap();
+        let d = NonZeroUsize::try_from(4usize).unwrap();
+        let mut wide = CountMinSketch::new(w, d);
+        let mut narrow = CountMinSketch::<u16>::with_counter(w, d);
+        for i in 0..2_000u32 {
+            wide.increment(&(i % 300));
+            narrow.increment(&(i % 300));
+        }
+        for i in 0..400u32 {
+            assert_eq!(narrow.estimate(&i), wide.estimate(&i));
+        }
+        narrow.clear();
+        assert_eq!(narrow.estimate(&0u32), 0);
+    }
+
+    #[test]
+    fn test_quantize_rounds_up() {
+        let mut cms = CountMinSketch::new(NonZeroUsize::try_from(256usize).unwrap(),NonZeroUsize::try_from(4usize).unwrap());
+        for i in 0..1_000u32 {
+            cms.increment(&(i % 97));
+        }
+        cms.increment_by("max", u64::MAX - 3);
+        let before: Vec<u64> = (0..97u32).map(|k| cms.estimate(&k)).collect();
+
+        cms.quantize(10);
+
+        let mut bytes = cms.to_bytes();
+        let cells = bytes.split_off(SketchHeader::LEN);
+        for cell in cells.chunks(8).map(|c| u64::from_le_bytes(c.try_into().unwrap())) {
+            assert!(cell % 10 == 0 || cell == u64::MAX, "{cell}");
+        }
+        for (k, &estimate) in before.iter().enumerate() {
+            assert!(cms.estimate(&(k as u32)) >= estimate);
+            assert!(cms.estimate(&(k as u32)) < estimate + 10);
+        }
+        assert_eq!(cms.estimate("max"), u64::MAX);
+    }
+
+    #[test]
+    fn test_total_tracks_increments() {
+        let w = NonZeroUsize::try_from(128usize).unwrap();
+        let d = NonZeroUsize::try_from(4usize).unwrap();
+        let mut cms = CountMinSketch::new(w, d);
+        for i in 0..50u32 {
+            cms.increment(&(i % 7));
+        }
+        assert_eq!(cms.total(), 50);
+
+        let mut other = CountMinSketch::new(w, d);
+        other.increment_by("bulk", u64::MAX);
+        cms.merge(&other).expect("Merge should succeed");
+        assert_eq!(cms.total(), u64::MAX);
+
+        cms.clear();
+        assert_eq!(cms.total(), 0);
+    }
+
+    #[test]
+    fn test_frequency() {
+        let mut cms = CountMinSketch::new(NonZeroUsize::try_from(1024usize).unwrap(),NonZeroUsize::try_from(4usize).unwrap());
+        assert_eq!(cms.frequency("a"), 0.0);
+
+        for _ in 0..3 { cms.increment("a"); }
+        cms.increment("b");
+
+        assert!((cms.frequency("a") - 0.75).abs() < 1e-9);
+        assert!((cms.frequency("b") - 0.25).abs() < 1e-9);
+        assert_eq!(cms.frequency("c"), 0.0);
+    }
+
+    #[test]
+    fn test_estimate_map() {
+        let mut cms = CountMinSketch::new(NonZeroUsize::try_from(1024usize).unwrap(),NonZeroUsize::try_from(4usize).unwrap());
+        for _ in 0..6 { cms.increment("busy"); }
+        cms.increment("quiet");
+
+        assert!(cms.estimate_map("busy", |c| c > 5));
+        assert!(!cms.estimate_map("quiet", |c| c > 5));
+        assert_eq!(cms.estimate_map("busy", |c| format!("{c} hits")), "6 hits");
+    }
+
+    #[test]
+    fn test_clone_is_independent() {
+        let mut cms = CountMinSketch::with_seed(NonZeroUsize::try_from(256usize).unwrap(),NonZeroUsize::try_from(4usize).unwrap(), 99);
+        for i in 0..1_000u32 {
+            cms.increment(&(i % 50));
+        }
+
+        let snapshot = cms.clone();
+        assert_eq!(snapshot.get_seeds(), cms.get_seeds());
+        for i in 0..100u32 {
+            assert_eq!(snapshot.estimate(&i), cms.estimate(&i));
+        }
+
+        for _ in 0..10 { cms.increment(&7u32); }
+        assert_eq!(cms.estimate(&7u32), snapshot.estimate(&7u32) + 10);
+        assert_eq!(snapshot.total(), 1_000);
+        assert_eq!(snapshot.changed_cells(&cms).unwrap().count(), 4);
+    }
+
+    #[test]
+    fn test_try_merge_all_reports_index() {
+        let d = NonZeroUsize::try_from(4usize).unwrap();
+        let shard = |w: usize, key: &str| {
+            let mut cms = CountMinSketch::new(NonZeroUsize::try_from(w).unwrap(), d);
+            cms.increment(key);
+            cms
+        };
+
+        let good: Vec<CountMinSketch> = (0..5).map(|i| shard(256, &format!("k{i}"))).collect();
+        let merged = CountMinSketch::try_merge_all(&good).expect("Merge should succeed");
+        assert_eq!(merged.total(), 5);
+        assert_eq!(merged.estimate("k3"), 1);
+
+        let mut batch = good;
+        batch[3] = shard(512, "k3");
+        assert_eq!(CountMinSketch::try_merge_all(&batch).err(), Some((3, SketchError::IncompatibleDimensions { left: (256, 4), right: (512, 4) })));
+        assert_eq!(CountMinSketch::try_merge_all(&[]).err(), Some((0, SketchError::Empty)));
+    }
+
+    #[test]
+    fn test_decrement() {
+        let mut cms = CountMinSketch::new(NonZeroUsize::try_from(4096usize).unwrap(),NonZeroUsize::try_from(4usize).unwrap());
+        for _ in 0..10 { cms.increment("expiring"); }
+        for _ in 0..3 { cms.decrement("expiring"); }
+        assert_eq!(cms.estimate("expiring"), 7);
+        assert_eq!(cms.total(), 7);
+
+        cms.decrement_by("expiring", 100);
+        assert_eq!(cms.estimate("expiring"), 0);
+        assert_eq!(cms.total(), 0);
+    }
+
+    #[test]
+    fn test_estimate_data_bound() {
+        let mut cms = CountMinSketch::new(NonZeroUsize::try_from(4096usize).unwrap(),NonZeroUsize::try_from(4usize).unwrap());
+        for i in 0..2_000u32 {
+            cms.increment(&(i % 400));
+        }
+        for _ in 0..50 { cms.increment("isolated"); }
+
+        let global_bound = (cms.actual_epsilon() * cms.total() as f64).ceil() as u64;
+        assert!(global_bound > 0);
+        assert!(cms.estimate_data_bound("isolated") <= 1);
+        assert!(cms.estimate_data_bound("isolated") < global_bound);
+
+        // The bound never exceeds the estimate itself
+        let mut crowded = CountMinSketch::new(NonZeroUsize::try_from(8usize).unwrap(),NonZeroUsize::try_from(4usize).unwrap());
+        crowded.increment_by("heavy", 1_000);
+        crowded.increment("light");
+        assert!(crowded.estimate_data_bound("light") <= crowded.estimate("light"));
+    }
+
+    #[test]
+    fn test_average_of_replicas() {
+        let w = NonZeroUsize::try_from(256usize).unwrap();
+        let d = NonZeroUsize::try_from(4usize).unwrap();
+        let mut replica = CountMinSketch::new(w, d);
+        for i in 0..3_000u32 {
+            replica.increment(&(i % 321));
+        }
+
+        let copies = [replica.clone(), replica.clone(), replica.clone()];
+        let averaged = CountMinSketch::average(&[&copies[0], &copies[1], &copies[2]]).unwrap();
+        assert_eq!(averaged.l1_distance(&replica), Ok(0));
+        assert_eq!(averaged.total(), replica.total());
+
+        let (mut low, mut high) = (CountMinSketch::new(w, d), CountMinSketch::new(w, d));
+        low.increment_by("key", 2);
+        high.increment_by("key", 4);
+        assert_eq!(CountMinSketch::average(&[&low, &high]).unwrap().estimate("key"), 3);
+
+        assert_eq!(CountMinSketch::average(&[]).err(), Some(SketchError::Empty));
+        let seeded = CountMinSketch::with_seed(w, d, 1);
+        assert_eq!(CountMinSketch::average(&[&low, &seeded]).err(), Some(SketchError::IncompatibleSeeds));
+    }
+
+    #[test]
+    fn test_dominant_cell() {
+        let mut cms = CountMinSketch::new(NonZeroUsize::try_from(8usize).unwrap(), NonZeroUsize::try_from(4usize).unwrap());
+        assert_eq!(cms.dominant_cell(), (0, 0, 0));
+
+        cms.set_cell(3, 7);
+        cms.set_cell(2 * 8 + 5, 42);
+        cms.set_cell(3 * 8 + 1, 41);
+        assert_eq!(cms.dominant_cell(), (2, 5, 42));
+
+        cms.set_cell(3 * 8 + 6, 42);
+        assert_eq!(cms.dominant_cell(), (2, 5, 42));
+    }
+
+    #[test]
+    fn test_estimate_mean_reduces_bias_for_rare_items() {
+        let mut cms = CountMinSketch::new(NonZeroUsize::try_from(256usize).unwrap(), NonZeroUsize::try_from(4usize).unwrap());
+        // Dense background: far more keys than columns
+        for i in 0..20_000u32 {
+            cms.increment_by(&i, 5);
+        }
+        let rare: Vec<String> = (0..100).map(|i| format!("rare{i}")).collect();
+        for key in &rare {
+            cms.increment_by(key, 3);
+        }
+
+        let min_error: u64 = rare.iter().map(|k| cms.estimate(k).abs_diff(3)).sum();
+        let mean_error: u64 = rare.iter().map(|k| cms.estimate_mean(k).abs_diff(3)).sum();
+        assert!(mean_error < min_error, "mean error {} should be below min error {}", mean_error, min_error);
+        for key in &rare {
+            assert!(cms.estimate_mean(key) <= cms.estimate(key));
+        }
+    }
+
+    #[test]
+    fn test_extend_and_from_iter_with_params() {
+        let words = vec!["apple", "banana", "apple", "cherry", "apple", "banana"];
+        let mut cms = CountMinSketch::new(NonZeroUsize::try_from(1024usize).unwrap(), NonZeroUsize::try_from(4usize).unwrap());
+        cms.extend(words.clone());
+        assert_eq!(cms.estimate("apple"), 3);
+        assert_eq!(cms.estimate("banana"), 2);
+        assert_eq!(cms.estimate("cherry"), 1);
+        assert_eq!(cms.total(), 6);
+
+        let collected = CountMinSketch::from_iter_with_params(0.01, 0.01, words);
+        assert_eq!(collected.estimate("apple"), 3);
+        assert_eq!(collected.total(), 6);
+    }
+
+    #[test]
+    fn test_estimate_batch_matches_estimate() {
+        let mut cms = CountMinSketch::new(NonZeroUsize::try_from(256usize).unwrap(), NonZeroUsize::try_from(4usize).unwrap());
+        cms.increment_by("a", 3);
+        cms.increment("b");
+
+        let batch = cms.estimate_batch(&["a", "b", "c"]);
+        assert_eq!(batch, vec![cms.estimate("a"), cms.estimate("b"), cms.estimate("c")]);
+        assert_eq!(batch, vec![3, 1, 0]);
+        assert!(cms.estimate_batch::<&str>(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_error_bound_grows_with_total() {
+        let mut cms = CountMinSketch::new(NonZeroUsize::try_from(1024usize).unwrap(), NonZeroUsize::try_from(4usize).unwrap());
+        assert_eq!(cms.error_bound(), 0);
+
+        cms.increment_by("a", 1_000);
+        let small = cms.error_bound();
+        assert_eq!(small, (std::f64::consts::E * 1_000.0 / 1024.0).ceil() as u64);
+        cms.increment_by("b", 100_000);
+        assert!(cms.error_bound() > small);
+        assert_eq!(cms.estimate_with_bound("a"), (cms.estimate("a"), cms.error_bound()));
+    }
+
+    #[test]
+    fn test_jaccard_similarity() {
+        let w = NonZeroUsize::try_from(1024usize).unwrap();
+        let d = NonZeroUsize::try_from(4usize).unwrap();
+        let mut a = CountMinSketch::new(w, d);
+        let mut b = CountMinSketch::new(w, d);
+        assert_eq!(a.jaccard_similarity(&b), Ok(0.0));
+
+        for i in 0..100u32 {
+            a.increment_by(&i, 3);
+            b.increment_by(&(i + 1_000), 3);
+        }
+        assert_eq!(a.jaccard_similarity(&a.clone()), Ok(1.0));
+        assert!(a.jaccard_similarity(&b).unwrap() < 0.1);
+
+        let mut half = CountMinSketch::new(w, d);
+        for i in 0..100u32 {
+            half.increment_by(&i, if i < 50 { 3 } else { 0 });
+        }
+        let sim = a.jaccard_similarity(&half).unwrap();
+        assert!((0.45..0.6).contains(&sim), "similarity was {}", sim);
+    }
+
+    #[test]
+    fn test_inner_product_estimates_join_size() {
+        let w = NonZeroUsize::try_from(1024usize).unwrap();
+        let d = NonZeroUsize::try_from(4usize).unwrap();
+        let mut a = CountMinSketch::new(w, d);
+        let mut b = CountMinSketch::new(w, d);
+        a.increment_by("x", 5);
+        b.increment_by("x", 3);
+        assert_eq!(a.inner_product(&b), Ok(15));
+
+        a.increment_by("y", 2);
+        b.increment_by("z", 7);
+        assert_eq!(a.inner_product(&b), Ok(15));
+
+        let other_seeds = CountMinSketch::with_seeds(w, d, [9, 9, 9, 9]);
+        assert_eq!(a.inner_product(&other_seeds), Err(SketchError::IncompatibleSeeds));
+    }
+
+    #[test]
+    fn test_saturation_detection() {
+        let mut cms = CountMinSketch::new(NonZeroUsize::try_from(256usize).unwrap(), NonZeroUsize::try_from(4usize).unwrap());
+        cms.increment("a");
+        assert!(!cms.is_saturated());
+        assert_eq!(cms.saturated_cells(), 0);
+
+        cms.increment_by("key", u64::MAX);
+        assert!(cms.is_saturated());
+        assert_eq!(cms.saturated_cells(), cms.get_depth());
+
+        let mut narrow = CountMinSketch::<u8>::with_counter(NonZeroUsize::try_from(256usize).unwrap(), NonZeroUsize::try_from(4usize).unwrap());
+        narrow.increment_by("key", 300);
+        assert_eq!(narrow.saturated_cells(), 4);
+    }
+
+    #[test]
+    fn test_memory_bytes_reflects_rounded_table() {
+        let cms = CountMinSketch::new(NonZeroUsize::try_from(1000usize).unwrap(), NonZeroUsize::try_from(8usize).unwrap());
+        let overhead = std::mem::size_of::<CountMinSketch>();
+        assert_eq!(cms.memory_bytes() - overhead, 1024 * 8 * 8);
+
+        let narrow = CountMinSketch::<u16>::with_counter(NonZeroUsize::try_from(1000usize).unwrap(), NonZeroUsize::try_from(8usize).unwrap());
+        assert_eq!(narrow.memory_bytes() - std::mem::size_of::<CountMinSketch<u16>>(), 1024 * 8 * 2);
+    }
+
+    #[test]
+    fn test_try_with_params_limits_table_size() {
+        let cms = CountMinSketch::try_with_params(0.01, 0.01).unwrap();
+        assert_eq!((cms.get_width(), cms.get_depth()), (CountMinSketch::with_params(0.01, 0.01).get_width(), 8));
+
+        assert!(matches!(CountMinSketch::try_with_params(1e-12, 1e-12), Err(SketchError::TooLarge { bytes }) if bytes > 1 << 30));
+        // 2^25 columns x 8 rows x 8 bytes
+        assert_eq!(CountMinSketch::try_with_params(1e-7, 0.01).err(), Some(SketchError::TooLarge { bytes: 1 << 31 }));
+        assert_eq!(CountMinSketch::try_with_params_limit(0.01, 0.01, 1024).err(), Some(SketchError::TooLarge { bytes: 512 * 8 * 8 }));
+        // Ratios whose size saturates or is infinite are rejected instead of overflowing
+        assert_eq!(CountMinSketch::try_with_params(1e-40, 0.5).err(), Some(SketchError::TooLarge { bytes: u128::MAX }));
+        assert_eq!(CountMinSketch::try_with_params(0.5, 1e-320).err(), Some(SketchError::TooLarge { bytes: u128::MAX }));
+        assert_eq!(CountMinSketch::try_with_params(f64::MIN_POSITIVE, f64::MIN_POSITIVE).err(), Some(SketchError::TooLarge { bytes: u128::MAX }));
+        assert_eq!(CountMinSketch::try_with_params(0.0, 0.01).err(), Some(SketchError::InvalidParameter("epsilon")));
+        assert_eq!(CountMinSketch::try_with_params(0.01, 1.0).err(), Some(SketchError::InvalidParameter("delta")));
+    }
+
+    #[test]
+    fn test_subtract_reverts_merge() {
+        let w = NonZeroUsize::try_from(256usize).unwrap();
+        let d = NonZeroUsize::try_from(4usize).unwrap();
+        let mut a = CountMinSketch::new(w, d);
+        let mut b = CountMinSketch::new(w, d);
+        for i in 0..500u32 {
+            a.increment_by(&i, (i % 7) as u64 + 1);
+            b.increment_by(&(i * 3), 2);
+        }
+        let before: Vec<u64> = (0..1_500u32).map(|i| a.estimate(&i)).collect();
+        let total = a.total();
+
+        a.merge(&b).unwrap();
+        a.subtract(&b).unwrap();
+        assert_eq!((0..1_500u32).map(|i| a.estimate(&i)).collect::<Vec<_>>(), before);
+        assert_eq!(a.total(), total);
+
+        let reseeded = CountMinSketch::with_seeds(w, d, [4, 3, 2, 1]);
+        assert_eq!(a.subtract(&reseeded), Err(SketchError::IncompatibleSeeds));
+    }
+
+    #[test]
+    fn test_partial_eq_compares_contents() {
+        let w = NonZeroUsize::try_from(256usize).unwrap();
+        let d = NonZeroUsize::try_from(4usize).unwrap();
+        let mut a = CountMinSketch::new(w, d);
+        let mut b = CountMinSketch::new(w, d);
+        for key in ["x", "y", "x", "z"] {
+            a.increment(key);
+            b.increment(key);
+        }
+        assert!(a == b);
+        assert!(CountMinSketch::from_bytes(&a.to_bytes()).unwrap() == a);
+
+        b.increment("x");
+        assert!(a != b);
+        assert!(CountMinSketch::new(w, d) != CountMinSketch::with_seeds(w, d, [4, 3, 2, 1]));
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn test_merge_all_parallel() {
+        let w = NonZeroUsize::try_from(256usize).unwrap();
+        let d = NonZeroUsize::try_from(4usize).unwrap();
+        let shard = || {
+            let mut cms = CountMinSketch::new(w, d);
+            cms.increment("item");
+            cms
+        };
+        let shards: Vec<CountMinSketch> = (0..16).map(|_| shard()).collect();
+        let merged = CountMinSketch::merge_all(&shards).unwrap();
+        assert_eq!(merged.estimate("item"), 16);
+        assert_eq!(merged.total(), 16);
+        assert!(merged == CountMinSketch::try_merge_all(&shards).unwrap());
+
+        let mut mixed = shards;
+        mixed.push(CountMinSketch::with_seeds(w, d, [4, 3, 2, 1]));
+        assert_eq!(CountMinSketch::merge_all(&mixed).err(), Some(SketchError::IncompatibleSeeds));
+        assert_eq!(CountMinSketch::merge_all(&[]).err(), Some(SketchError::Empty));
+    }
+
+    #[test]
+    fn test_rows_yields_depth_slices_of_width() {
+        let mut cms = CountMinSketch::new(NonZeroUsize::try_from(100usize).unwrap(), NonZeroUsize::try_from(3usize).unwrap());
+        cms.increment_by("key", 7);
+        assert_eq!(cms.rows().count(), cms.get_depth());
+        assert!(cms.rows().all(|row| row.len() == cms.get_width()));
+        // Every row holds the whole mass
+        assert!(cms.rows().all(|row| row.iter().sum::<u64>() == 7));
+    }
+
+    #[test]
+    fn test_fill_ratio() {
+        let mut cms = CountMinSketch::new(NonZeroUsize::try_from(256usize).unwrap(), NonZeroUsize::try_from(4usize).unwrap());
+        assert_eq!(cms.fill_ratio(), 0.0);
+
+        cms.increment("one");
+        assert_eq!(cms.fill_ratio(), 4.0 / 1024.0);
+
+        for i in 0..10_000u32 {
+            cms.increment(&i);
+        }
+        assert!(cms.fill_ratio() > 0.99);
+    }
+
+    #[test]
+    fn test_estimate_reports_saturated_counters() {
+        for depth in [1usize, 4] {
+            let mut cms = CountMinSketch::new(NonZeroUsize::try_from(64usize).unwrap(), NonZeroUsize::try_from(depth).unwrap());
+            cms.increment_by("hot", u64::MAX);
+            cms.increment("hot");
+            assert!(cms.is_saturated());
+            assert_eq!(cms.estimate("hot"), u64::MAX);
+            assert!(cms.contains("hot"));
+        }
+    }
+
+    #[test]
+    fn test_no_aes_hash_cell_indices() {
+        // Known answer that holds on every build, with or without the `aes` target feature
+        let (w, d) = (NonZeroUsize::new(1024).unwrap(), NonZeroUsize::new(4).unwrap());
+        let mut cms = CountMinSketch::with_seeds_no_aes(w, d, [1, 2, 3, 4]);
+        let empty = CountMinSketch::with_seeds_no_aes(w, d, [1, 2, 3, 4]);
+        assert_eq!(cms.hash_algo(), HashAlgo::FxHash);
+        cms.increment("portable");
+
+        let cells: Vec<usize> = cms.changed_cells(&empty).unwrap().map(|(index, _)| index).collect();
+        assert_eq!(cells, [634, 1143, 2676, 3185]);
+    }
+
+    #[test]
+    fn test_portable_algo_cell_indices() {
+        // Known answers for the hashers documented as portable: integers are hashed as
+        // fixed-width little-endian bytes, so these cells hold on every target
+        let (w, d) = (NonZeroUsize::new(1024).unwrap(), NonZeroUsize::new(2).unwrap());
+        let cells = |algo, key: &(&str, usize, i32, u16, u128)| -> Vec<usize> {
+            let empty = CountMinSketch::with_hash_algo(w, d, [1, 2, 3, 4], algo);
+            let mut cms = CountMinSketch::with_hash_algo(w, d, [1, 2, 3, 4], algo);
+            cms.increment(key);
+            cms.changed_cells(&empty).unwrap().map(|(index, _)| index).collect()
+        };
+        let key = ("portable", 7usize, -3i32, 0xABCDu16, 1u128 << 100);
+
+        assert_eq!(cells(HashAlgo::SipHash, &key), [614, 1949]);
+        assert_eq!(cells(HashAlgo::FxHash, &key), [400, 1853]);
+    }
+
+    #[test]
+    fn test_estimates_unchanged_by_prefetch() {
+        // ConcurrentCountMinSketch never prefetches, so it is an independent reference for
+        // the cells `increment` and `estimate` touch with the `prefetch` feature
+        let (w, d) = (NonZeroUsize::new(1 << 16).unwrap(), NonZeroUsize::new(16).unwrap());
+        let mut cms = CountMinSketch::with_seeds(w, d, [5, 6, 7, 8]);
+        let reference = ConcurrentCountMinSketch::with_seeds(w, d, [5, 6, 7, 8]);
+        for i in 0..200_000u64 {
+            cms.increment(&(i % 70_001));
+            reference.increment(&(i % 70_001));
+        }
+
+        for key in 0..80_000u64 {
+            assert_eq!(cms.estimate(&key), reference.estimate(&key));
+        }
+        assert!(cms == reference.snapshot());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,66 @@
+#[cfg(test)]
+mod tests {
+    use count_min_sketch_rs::TopKSketch;
+    use rand::prelude::*;
+    use rand_distr::{Distribution, Zipf};
+    use std::num::NonZeroUsize;
+
+    #[test]
+    fn test_top_k_on_skewed_stream() {
+        let mut sketch = TopKSketch::with_params(0.001, 0.01, NonZeroUsize::new(10).unwrap());
+        let mut truth = std::collections::HashMap::new();
+
+        let mut rng = StdRng::seed_from_u64(3);
+        let zipf = Zipf::new(5_000.0, 1.2).unwrap();
+        for _ in 0..50_000 {
+            let key = format!("item-{}", zipf.sample(&mut rng) as u64);
+            sketch.increment(&key);
+            *truth.entry(key).or_insert(0u64) += 1;
+        }
+
+        let top = sketch.top_k();
+        assert_eq!(top.len(), 10);
+        assert!(top.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+
+        let heaviest = truth.iter().max_by_key(|&(_, &count)| count).unwrap().0;
+        assert_eq!(top[0].0, heaviest.as_bytes());
+        assert_eq!(top[0].1, sketch.estimate(heaviest));
+    }
+
+    #[test]
+    fn test_top_k_evicts_smallest() {
+        let mut sketch = TopKSketch::new(NonZeroUsize::new(1024).unwrap(), NonZeroUsize::new(4).unwrap(), NonZeroUsize::new(2).unwrap());
+        for (key, count) in [("a", 5), ("b", 1), ("c", 3)] {
+            for _ in 0..count {
+                sketch.increment(key);
+            }
+        }
+
+        assert_eq!(sketch.top_k(), vec![(b"a".to_vec(), 5), (b"c".to_vec(), 3)]);
+
+        for _ in 0..10 {
+            sketch.increment(b"b".as_slice());
+        }
+        assert_eq!(sketch.top_k(), vec![(b"b".to_vec(), 11), (b"a".to_vec(), 5)]);
+    }
+
+    #[test]
+    fn test_retain_top_k() {
+        let mut sketch = TopKSketch::new(NonZeroUsize::new(1024).unwrap(), NonZeroUsize::new(4).unwrap(), NonZeroUsize::new(5).unwrap());
+        for (key, count) in [("a", 9), ("b", 2), ("c", 7), ("d", 1), ("e", 5)] {
+            for _ in 0..count {
+                sketch.increment(key);
+            }
+        }
+
+        sketch.retain_top_k(3);
+        assert_eq!(sketch.top_k(), vec![(b"a".to_vec(), 9), (b"c".to_vec(), 7), (b"e".to_vec(), 5)]);
+
+        // Freed slots are refilled by later keys
+        sketch.increment("f");
+        assert_eq!(sketch.top_k().len(), 4);
+
+        sketch.retain_top_k_with_decay(2, 0.5);
+        assert_eq!(sketch.top_k(), vec![(b"a".to_vec(), 4), (b"c".to_vec(), 3)]);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,48 @@
+#[cfg(test)]
+mod tests {
+    use count_min_sketch_rs::ValueSketch;
+    use std::num::NonZeroUsize;
+
+    fn setup_sketch() -> ValueSketch {
+        ValueSketch::new(
+            NonZeroUsize::new(256).unwrap(),
+            NonZeroUsize::new(4).unwrap(),
+            0.0,
+            1000.0,
+            NonZeroUsize::new(100).unwrap()
+        )
+    }
+
+    #[test]
+    fn test_value_quantiles() {
+        let mut vs = setup_sketch();
+
+        // latency.api: uniform values 1..=1000
+        for v in 1..=1000 {
+            vs.insert("latency.api", v as f64);
+        }
+        // Unrelated metric with a very different distribution
+        for _ in 0..500 {
+            vs.insert("latency.db", 5.0);
+        }
+
+        let (freq, p50, p99) = vs.query("latency.api");
+        assert!(freq >= 1000);
+        // Bin width is 10, so quantiles are accurate to within one bin
+        assert!((p50 - 500.0).abs() <= 10.0, "p50 was {}", p50);
+        assert!((p99 - 990.0).abs() <= 10.0, "p99 was {}", p99);
+
+        let (freq, p50, _) = vs.query("latency.db");
+        assert!(freq >= 500);
+        assert!(p50 < 10.0, "p50 was {}", p50);
+    }
+
+    #[test]
+    fn test_value_query_absent() {
+        let vs = setup_sketch();
+        let (freq, p50, p99) = vs.query("missing");
+        assert_eq!(freq, 0);
+        assert!(p50.is_nan() && p99.is_nan());
+        assert_eq!(vs.quantile("missing", 0.5), None);
+    }
+}
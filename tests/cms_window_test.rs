@@ -0,0 +1,37 @@
+#[cfg(test)]
+mod tests {
+    use count_min_sketch_rs::WindowedCountMinSketch;
+    use std::num::NonZeroUsize;
+
+    fn window(buckets: usize) -> WindowedCountMinSketch {
+        WindowedCountMinSketch::new(NonZeroUsize::new(1024).unwrap(), NonZeroUsize::new(4).unwrap(), NonZeroUsize::new(buckets).unwrap())
+    }
+
+    #[test]
+    fn test_window_sums_buckets() {
+        let mut cms = window(3);
+        assert_eq!(cms.buckets(), 3);
+        cms.increment_by("key", 5);
+        cms.advance();
+        cms.increment("key");
+        cms.advance();
+        cms.increment_by("key", 2);
+        assert_eq!(cms.estimate("key"), 8);
+
+        // The bucket holding the first 5 is the oldest and is reused
+        cms.advance();
+        assert_eq!(cms.estimate("key"), 3);
+    }
+
+    #[test]
+    fn test_window_forgets_after_full_rotation() {
+        let mut cms = window(5);
+        cms.increment_by("key", 10);
+        for advanced in 1..5 {
+            cms.advance();
+            assert_eq!(cms.estimate("key"), 10, "still in the window after {} advances", advanced);
+        }
+        cms.advance();
+        assert_eq!(cms.estimate("key"), 0);
+    }
+}
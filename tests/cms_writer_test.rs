@@ -0,0 +1,39 @@
+#[cfg(test)]
+mod tests {
+    use count_min_sketch_rs::{CountMinSketch, SketchWriter, Tokenize};
+    use std::io::{self, BufReader, Write};
+    use std::num::NonZeroUsize;
+
+    fn sketch() -> CountMinSketch {
+        CountMinSketch::new(NonZeroUsize::new(1024).unwrap(), NonZeroUsize::new(4).unwrap())
+    }
+
+    #[test]
+    fn test_copy_lines_into_sketch() {
+        let mut cms = sketch();
+        // Reads of 3 bytes split most lines across writes
+        let mut reader = BufReader::with_capacity(3, "apple\nbanana\napple\n\ncherry\napple".as_bytes());
+        let mut writer = SketchWriter::new(&mut cms, Tokenize::Lines);
+        io::copy(&mut reader, &mut writer).unwrap();
+        writer.finish();
+
+        assert_eq!(cms.estimate(b"apple".as_slice()), 3);
+        assert_eq!(cms.estimate(b"banana".as_slice()), 1);
+        assert_eq!(cms.estimate(b"cherry".as_slice()), 1);
+        assert_eq!(cms.estimate(b"".as_slice()), 0);
+        assert_eq!(cms.total(), 5);
+    }
+
+    #[test]
+    fn test_write_whole_chunks() {
+        let mut cms = sketch();
+        let mut writer = SketchWriter::new(&mut cms, Tokenize::Chunk);
+        writer.write_all(b"frame-a").unwrap();
+        writer.write_all(b"frame-a").unwrap();
+        writer.write_all(b"frame-b\n").unwrap();
+        writer.finish();
+
+        assert_eq!(cms.estimate(b"frame-a".as_slice()), 2);
+        assert_eq!(cms.estimate(b"frame-b\n".as_slice()), 1);
+    }
+}